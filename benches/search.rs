@@ -0,0 +1,65 @@
+// 在一个较大的合成日志上比较逐行 contains 的 search 与 memmem 快速路径：
+// cargo bench --bench search
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use hello_world::minigrep::{self, fast};
+
+const LINES: usize = 1_000_000;
+const ROUNDS: usize = 5;
+
+// 每 1000 行有一行 ERROR，其余都是长短不一的普通日志
+fn synthetic_log() -> String {
+    let mut log = String::new();
+    for i in 0..LINES {
+        let level = if i % 1000 == 0 { "ERROR" } else { "INFO" };
+        log.push_str(&format!(
+            "2024-01-01T00:{:02}:{:02} {} request id={} path=/api/v1/items/{} took {}ms\n",
+            i / 60 % 60,
+            i % 60,
+            level,
+            i,
+            i % 97,
+            i % 13 * 7
+        ));
+        if i % 10 == 0 {
+            log.push_str("ok\n");
+        }
+    }
+    log
+}
+
+// 运行 ROUNDS 次，返回最快的一次
+fn bench<'a>(name: &str, size: usize, f: impl Fn() -> Vec<&'a str>) -> Vec<&'a str> {
+    let mut best = Duration::MAX;
+    let mut results = Vec::new();
+    for _ in 0..ROUNDS {
+        let start = Instant::now();
+        results = black_box(f());
+        best = best.min(start.elapsed());
+    }
+    println!(
+        "{:<10} {:>10.2?}  {:>8.0} MB/s",
+        name,
+        best,
+        size as f64 / best.as_secs_f64() / 1e6
+    );
+    results
+}
+
+fn main() {
+    let log = synthetic_log();
+    println!(
+        "{} MB, {} lines, best of {} rounds",
+        log.len() / 1_000_000,
+        log.lines().count(),
+        ROUNDS
+    );
+
+    for key in ["ERROR", "id=999999 "] {
+        println!("key: {:?}", key);
+        let line_by_line = bench("contains", log.len(), || minigrep::search(key, &log));
+        let memmem = bench("memmem", log.len(), || fast::search(key, &log));
+        assert_eq!(line_by_line, memmem);
+    }
+}
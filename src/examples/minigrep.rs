@@ -1,6 +1,6 @@
 use std::{env, process};
 
-use hello_world::minigrep::{self, Config};
+use hello_world::minigrep::{self, cancel::Cancelled, output::Format, Config};
 
 fn main() {
     // env::Args 实现了 Iterator 特征，所以 env::args() 返回的实际上是一个迭代器
@@ -12,11 +12,30 @@ fn main() {
         process::exit(1);
     });
 
-    println!("Searching for [{}] In file [{}]", config.key, config.file);
+    // JSON 格式和 -l/-L 的输出需要能被直接解析，交互模式和服务模式下没有 key，都不输出提示信息
+    if config.format == Format::Text
+        && config.list_files.is_none()
+        && !config.interactive
+        && config.serve.is_none()
+    {
+        println!(
+            "Searching for [{}] In file [{}]",
+            config.key,
+            if config.files.is_empty() {
+                "-".to_string()
+            } else {
+                config.files.join(", ")
+            }
+        );
+    }
 
     // 只匹配run函数返回的错误，因为我们不关心Ok值
     if let Err(error) = minigrep::run(config) {
         eprintln!("Failed to run application: {error}");
+        // 与 timeout 命令一样，超时或被取消时以 124 退出，方便脚本区分
+        if error.is::<Cancelled>() {
+            process::exit(124);
+        }
         process::exit(1);
     }
 }
@@ -0,0 +1,191 @@
+/// 线程安全的持久化链表：与 third_list 相同的不可变链表，只是把 Rc 换成了 Arc，
+/// 同一个链表的快照（以及共享的尾部）可以在多个线程之间传递和共享
+use std::sync::Arc;
+
+type Link<T> = Option<Arc<Node<T>>>;
+
+#[derive(Debug)]
+struct Node<T> {
+    value: T,
+    next: Link<T>,
+}
+
+// Arc<Node<T>> 在 T: Send + Sync 时才是 Send + Sync，List 自动满足同样的条件，不需要 unsafe impl
+#[derive(Debug)]
+pub struct List<T> {
+    head: Link<T>,
+}
+
+impl<T> Default for List<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// 克隆只增加头节点的引用计数，得到的是共享全部节点的快照
+impl<T> Clone for List<T> {
+    fn clone(&self) -> Self {
+        List {
+            head: self.head.clone(),
+        }
+    }
+}
+
+impl<T> List<T> {
+    pub fn new() -> Self {
+        List { head: None }
+    }
+
+    pub fn prepend(&self, value: T) -> List<T> {
+        List {
+            head: Some(Arc::new(Node {
+                value,
+                next: self.head.clone(),
+            })),
+        }
+    }
+
+    pub fn tail(&self) -> List<T> {
+        List {
+            head: self.head.as_ref().and_then(|node| node.next.clone()),
+        }
+    }
+
+    pub fn peek(&self) -> Option<&T> {
+        self.head.as_ref().map(|node| &node.value)
+    }
+
+    /// 取出头部的元素，链表变为原来的 tail：链表独占头节点时把值移动出来，否则克隆一份
+    pub fn pop(&mut self) -> Option<T>
+    where
+        T: Clone,
+    {
+        self.head.take().map(|node| match Arc::try_unwrap(node) {
+            Ok(node) => {
+                self.head = node.next;
+                node.value
+            }
+            Err(node) => {
+                self.head = node.next.clone();
+                node.value.clone()
+            }
+        })
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            next: self.head.as_deref(),
+        }
+    }
+}
+
+pub struct Iter<'a, T> {
+    next: Option<&'a Node<T>>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next.map(|node| {
+            self.next = node.next.as_deref();
+            &node.value
+        })
+    }
+}
+
+impl<T> Drop for List<T> {
+    fn drop(&mut self) {
+        // 和 third_list 一样逐个释放独占的节点，避免长链表递归 drop 导致栈溢出。
+        // 但这里不能用 try_unwrap：两个线程同时释放共享同一个节点的最后两个链表时，
+        // 双方的 try_unwrap 可能都失败，最后一个 Arc 被普通地 drop，后面的节点又变成了递归释放。
+        // into_inner 保证引用计数降到 0 的那一方一定拿到节点，由它继续向后释放
+        let mut head = self.head.take();
+        while let Some(node) = head {
+            match Arc::into_inner(node) {
+                Some(mut node) => head = node.next.take(),
+                None => break,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::List;
+    use std::sync::{Arc, Barrier};
+    use std::thread;
+
+    const THREAD_COUNT: usize = 6;
+
+    #[test]
+    fn test_basic() {
+        let list = List::new();
+        assert_eq!(list.peek(), None);
+
+        let list = list.prepend(1).prepend(2).prepend(3);
+        assert_eq!(list.peek(), Some(&3));
+        assert_eq!(list.iter().collect::<Vec<_>>(), vec![&3, &2, &1]);
+
+        let list = list.tail();
+        assert_eq!(list.peek(), Some(&2));
+
+        let list = list.tail().tail();
+        assert_eq!(list.peek(), None);
+        assert_eq!(list.tail().iter().next(), None);
+    }
+
+    #[test]
+    fn test_send_sync() {
+        fn is_send_sync<T: Send + Sync>() {}
+        is_send_sync::<List<i32>>();
+        is_send_sync::<List<String>>();
+    }
+
+    #[test]
+    fn test_share_between_threads() {
+        let mut handles = Vec::with_capacity(THREAD_COUNT);
+        let shared = (0..3).fold(List::new(), |list, i| list.prepend(i));
+
+        for i in 0..THREAD_COUNT {
+            // 每个线程拿到同一个快照，在前面加上自己的节点，互不影响
+            let snapshot = shared.clone();
+            let handle = thread::spawn(move || {
+                let list = snapshot.prepend(i * 10);
+                list.iter().copied().collect::<Vec<_>>()
+            });
+            handles.push(handle);
+        }
+
+        for (i, handle) in handles.into_iter().enumerate() {
+            assert_eq!(handle.join().unwrap(), vec![i * 10, 2, 1, 0]);
+        }
+        assert_eq!(shared.iter().copied().collect::<Vec<_>>(), vec![2, 1, 0]);
+    }
+
+    #[test]
+    fn test_concurrent_drop_of_long_shared_tail() {
+        // 足够长的链表，递归释放会栈溢出
+        let len = 200_000;
+        let shared = (0..len).fold(List::new(), |list, i| list.prepend(i));
+        let barrier = Arc::new(Barrier::new(THREAD_COUNT));
+        let mut handles = Vec::with_capacity(THREAD_COUNT);
+
+        for i in 0..THREAD_COUNT {
+            let list = shared.prepend(len + i);
+            let b = barrier.clone();
+            let handle = thread::spawn(move || {
+                assert_eq!(list.iter().count(), len + 1);
+                // 所有线程同时释放各自的链表，共享的尾部只能被最后一个线程逐个释放
+                b.wait();
+                drop(list);
+            });
+            handles.push(handle);
+        }
+        drop(shared);
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+    }
+}
@@ -0,0 +1,1028 @@
+/// 基于裸指针的双向链表。
+/// fourth_list 的每次操作都要修改引用计数、在运行时检查借用，弹出节点时还依赖 Rc::try_unwrap 不会失败；
+/// 这里节点由链表独占，用 NonNull 互相指向，借用规则由 &self / &mut self 在编译期保证。
+/// 所有的 unsafe 代码都集中在这个文件里，测试需要能在 Miri（cargo +nightly miri test）下通过，
+/// 文件只依赖 std，可以单独复制到一个空的 crate 中运行 Miri
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::mem;
+use std::ops::{Bound, RangeBounds};
+use std::ptr::NonNull;
+
+type Link<T> = Option<NonNull<Node<T>>>;
+
+struct Node<T> {
+    value: T,
+    prev: Link<T>,
+    next: Link<T>,
+}
+
+pub struct List<T> {
+    head: Link<T>,
+    tail: Link<T>,
+    len: usize,
+    // NonNull<Node<T>> 本身对 T 是协变的，但它不表示拥有 T，
+    // 加上 PhantomData<T> 告诉 drop 检查器链表被 drop 时会 drop 其中的 T
+    _marker: PhantomData<T>,
+}
+
+pub struct Iter<'a, T> {
+    head: Link<T>,
+    tail: Link<T>,
+    len: usize,
+    _marker: PhantomData<&'a T>,
+}
+
+// PhantomData<&'a mut T> 让 IterMut 对 T 不变，与 std::slice::IterMut 一致
+pub struct IterMut<'a, T> {
+    head: Link<T>,
+    tail: Link<T>,
+    len: usize,
+    _marker: PhantomData<&'a mut T>,
+}
+
+pub struct IntoIter<T> {
+    list: List<T>,
+}
+
+// 被移除的元素已经组成了一个独立的链表，借用原链表只是为了和 VecDeque::drain 的签名保持一致
+pub struct Drain<'a, T> {
+    list: List<T>,
+    _marker: PhantomData<&'a mut List<T>>,
+}
+
+impl<T> List<T> {
+    pub fn new() -> Self {
+        List {
+            head: None,
+            tail: None,
+            len: 0,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+
+    pub fn push_front(&mut self, value: T) {
+        // Box::leak 得到的指针一定不为空，之后由 pop_front/pop_back 中的 Box::from_raw 收回
+        let new_node = NonNull::from(Box::leak(Box::new(Node {
+            value,
+            prev: None,
+            next: self.head,
+        })));
+        match self.head {
+            // SAFETY: 链表中的节点都是有效的，并且只有链表自己能访问它们
+            Some(old_head) => unsafe { (*old_head.as_ptr()).prev = Some(new_node) },
+            None => self.tail = Some(new_node),
+        }
+        self.head = Some(new_node);
+        self.len += 1;
+    }
+
+    pub fn push_back(&mut self, value: T) {
+        let new_node = NonNull::from(Box::leak(Box::new(Node {
+            value,
+            prev: self.tail,
+            next: None,
+        })));
+        match self.tail {
+            // SAFETY: 同 push_front
+            Some(old_tail) => unsafe { (*old_tail.as_ptr()).next = Some(new_node) },
+            None => self.head = Some(new_node),
+        }
+        self.tail = Some(new_node);
+        self.len += 1;
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        // SAFETY: head 是链表中的节点
+        self.head.map(|node| unsafe { self.unlink(node) })
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        // SAFETY: tail 是链表中的节点
+        self.tail.map(|node| unsafe { self.unlink(node) })
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        // SAFETY: 返回的引用借用了 &self，期间链表不会被修改
+        self.head.map(|node| unsafe { &(*node.as_ptr()).value })
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        // SAFETY: 同 front
+        self.tail.map(|node| unsafe { &(*node.as_ptr()).value })
+    }
+
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        // SAFETY: 返回的引用借用了 &mut self，期间没有其他引用能访问这个节点
+        self.head.map(|node| unsafe { &mut (*node.as_ptr()).value })
+    }
+
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        // SAFETY: 同 front_mut
+        self.tail.map(|node| unsafe { &mut (*node.as_ptr()).value })
+    }
+
+    /// 第 index 个元素，从离 index 较近的一端开始查找，O(min(index, len - index))
+    pub fn get(&self, index: usize) -> Option<&T> {
+        // SAFETY: node_at 返回的是链表中的节点，引用借用了 &self
+        self.node_at(index)
+            .map(|node| unsafe { &(*node.as_ptr()).value })
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        // SAFETY: node_at 返回的是链表中的节点，引用借用了 &mut self
+        self.node_at(index)
+            .map(|node| unsafe { &mut (*node.as_ptr()).value })
+    }
+
+    pub fn contains(&self, value: &T) -> bool
+    where
+        T: PartialEq,
+    {
+        self.iter().any(|v| v == value)
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            head: self.head,
+            tail: self.tail,
+            len: self.len,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            head: self.head,
+            tail: self.tail,
+            len: self.len,
+            _marker: PhantomData,
+        }
+    }
+
+    /// 把 other 中的所有元素移动到链表的尾部，other 变为空链表，O(1)
+    pub fn append(&mut self, other: &mut List<T>) {
+        let Some(tail) = self.tail else {
+            mem::swap(self, other);
+            return;
+        };
+        if let Some(other_head) = other.head.take() {
+            // SAFETY: tail 和 other_head 分别是两个链表中的节点，连接之后 other 不再拥有它们
+            unsafe {
+                (*tail.as_ptr()).next = Some(other_head);
+                (*other_head.as_ptr()).prev = Some(tail);
+            }
+            self.tail = other.tail.take();
+            self.len += mem::take(&mut other.len);
+        }
+    }
+
+    /// 在 at 处把链表拆分成两段，返回 [at, len) 部分，链表中保留 [0, at) 部分。
+    /// at > len 时 panic
+    pub fn split_off(&mut self, at: usize) -> List<T> {
+        assert!(at <= self.len, "Cannot split off at a nonexistent index");
+        if at == 0 {
+            return mem::take(self);
+        }
+        // at - 1 < len，节点一定存在
+        let Some(last) = self.node_at(at - 1) else {
+            unreachable!()
+        };
+        // SAFETY: last 是链表中的节点，断开以后 last 之后的节点都属于新的链表
+        let head = unsafe { (*last.as_ptr()).next.take() };
+        let tail = match head {
+            Some(head) => {
+                // SAFETY: head 是原来 last 的下一个节点，现在是新链表的头节点
+                unsafe { (*head.as_ptr()).prev = None };
+                self.tail.replace(last)
+            }
+            None => None,
+        };
+        let len = mem::replace(&mut self.len, at) - at;
+        List {
+            head,
+            tail,
+            len,
+            _marker: PhantomData,
+        }
+    }
+
+    /// 只保留 f 返回 true 的元素，保持原来的顺序
+    pub fn retain(&mut self, mut f: impl FnMut(&T) -> bool) {
+        let mut current = self.head;
+        while let Some(node) = current {
+            // SAFETY: node 是链表中的节点，先记下下一个节点再删除它
+            unsafe {
+                current = (*node.as_ptr()).next;
+                if !f(&(*node.as_ptr()).value) {
+                    self.unlink(node);
+                }
+            }
+        }
+    }
+
+    /// 在 index 处插入元素，原来 index 及之后的元素向后移动。index > len 时 panic
+    pub fn insert(&mut self, index: usize, value: T) {
+        assert!(index <= self.len, "index out of bounds");
+        let Some(next) = self.node_at(index) else {
+            // index == len
+            self.push_back(value);
+            return;
+        };
+        // SAFETY: next 是链表中的节点，新节点插入到它和它原来的前一个节点之间
+        unsafe {
+            let prev = (*next.as_ptr()).prev;
+            let new_node = NonNull::from(Box::leak(Box::new(Node {
+                value,
+                prev,
+                next: Some(next),
+            })));
+            match prev {
+                Some(prev) => (*prev.as_ptr()).next = Some(new_node),
+                None => self.head = Some(new_node),
+            }
+            (*next.as_ptr()).prev = Some(new_node);
+        }
+        self.len += 1;
+    }
+
+    /// 删除并返回 index 处的元素，index 越界时返回 None
+    pub fn remove(&mut self, index: usize) -> Option<T> {
+        // SAFETY: node_at 返回的是链表中的节点，删除之后不再使用
+        self.node_at(index).map(|node| unsafe { self.unlink(node) })
+    }
+
+    /// 只保留前 len 个元素，len 不小于链表长度时什么都不做
+    pub fn truncate(&mut self, len: usize) {
+        while self.len > len {
+            self.pop_back();
+        }
+    }
+
+    /// 交换 i 和 j 处的元素，任意一个越界时 panic
+    pub fn swap(&mut self, i: usize, j: usize) {
+        assert!(i < self.len && j < self.len, "index out of bounds");
+        if i == j {
+            return;
+        }
+        let (Some(a), Some(b)) = (self.node_at(i), self.node_at(j)) else {
+            unreachable!()
+        };
+        // SAFETY: i != j，a 和 b 是链表中两个不同的节点，两个可变引用不会重叠
+        unsafe { mem::swap(&mut (*a.as_ptr()).value, &mut (*b.as_ptr()).value) }
+    }
+
+    /// 把前 n 个元素移动到链表的尾部，只修改链接，O(min(n, len - n))。n > len 时 panic
+    pub fn rotate_left(&mut self, n: usize) {
+        assert!(n <= self.len, "rotation amount out of bounds");
+        self.rotate_to(n);
+    }
+
+    /// 把后 n 个元素移动到链表的头部。n > len 时 panic
+    pub fn rotate_right(&mut self, n: usize) {
+        assert!(n <= self.len, "rotation amount out of bounds");
+        self.rotate_to(self.len - n);
+    }
+
+    /// 删除 range 中的元素，以迭代器的形式返回。
+    /// 元素在调用时就已经从链表中移除，没有迭代完的元素在 Drain 被 drop 时释放。
+    /// 范围的起点大于终点或者终点大于 len 时 panic
+    pub fn drain(&mut self, range: impl RangeBounds<usize>) -> Drain<'_, T> {
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end + 1,
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => self.len,
+        };
+        assert!(start <= end && end <= self.len, "drain range out of bounds");
+        let mut tail = self.split_off(end);
+        let drained = self.split_off(start);
+        self.append(&mut tail);
+        Drain {
+            list: drained,
+            _marker: PhantomData,
+        }
+    }
+
+    // 让第 n 个元素成为头节点，n 为 0 或者 len 时不需要移动
+    fn rotate_to(&mut self, n: usize) {
+        if n == 0 || n == self.len {
+            return;
+        }
+        let (Some(new_head), Some(head), Some(tail)) = (self.node_at(n), self.head, self.tail)
+        else {
+            unreachable!()
+        };
+        // SAFETY: 0 < n < len，new_head 不是头节点，一定有前一个节点；
+        // 先把链表首尾相连，再从 new_head 之前断开，所有的节点仍然属于这个链表
+        unsafe {
+            let new_tail = (*new_head.as_ptr()).prev.take();
+            if let Some(new_tail) = new_tail {
+                (*new_tail.as_ptr()).next = None;
+            }
+            (*tail.as_ptr()).next = Some(head);
+            (*head.as_ptr()).prev = Some(tail);
+            self.head = Some(new_head);
+            self.tail = new_tail;
+        }
+    }
+
+    fn node_at(&self, index: usize) -> Link<T> {
+        if index >= self.len {
+            return None;
+        }
+        // SAFETY: index < len，沿着链接走的每一步都落在链表中的节点上
+        unsafe {
+            if index < self.len / 2 {
+                let mut node = self.head?;
+                for _ in 0..index {
+                    node = (*node.as_ptr()).next?;
+                }
+                Some(node)
+            } else {
+                let mut node = self.tail?;
+                for _ in index + 1..self.len {
+                    node = (*node.as_ptr()).prev?;
+                }
+                Some(node)
+            }
+        }
+    }
+
+    /// 从链表中摘下 node 并释放它，返回其中的值。
+    /// SAFETY: node 必须是这个链表中的节点，并且之后不能再使用
+    unsafe fn unlink(&mut self, node: NonNull<Node<T>>) -> T {
+        let boxed = Box::from_raw(node.as_ptr());
+        match boxed.prev {
+            Some(prev) => (*prev.as_ptr()).next = boxed.next,
+            None => self.head = boxed.next,
+        }
+        match boxed.next {
+            Some(next) => (*next.as_ptr()).prev = boxed.prev,
+            None => self.tail = boxed.prev,
+        }
+        self.len -= 1;
+        boxed.value
+    }
+}
+
+impl<T> Default for List<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for List<T> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T: Clone> Clone for List<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T> Extend<T> for List<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push_back(value);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for List<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = List::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for List<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for List<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other)
+    }
+}
+
+impl<T: Eq> Eq for List<T> {}
+
+impl<T: PartialOrd> PartialOrd for List<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other)
+    }
+}
+
+impl<T: Ord> Ord for List<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other)
+    }
+}
+
+impl<T: Hash> Hash for List<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // 先写入长度，否则 [[1], [2]] 和 [[1, 2]] 这样嵌套的链表会得到相同的哈希值
+        self.len.hash(state);
+        for value in self {
+            value.hash(state);
+        }
+    }
+}
+
+// 链表独占所有的节点，和 Box<T>、Vec<T> 一样，T 能跨线程移动/共享时链表也可以
+unsafe impl<T: Send> Send for List<T> {}
+unsafe impl<T: Sync> Sync for List<T> {}
+
+// Iter 相当于 &T，IterMut 相当于 &mut T
+unsafe impl<T: Sync> Send for Iter<'_, T> {}
+unsafe impl<T: Sync> Sync for Iter<'_, T> {}
+unsafe impl<T: Send> Send for IterMut<'_, T> {}
+unsafe impl<T: Sync> Sync for IterMut<'_, T> {}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // 用剩余的长度判断是否结束，这样从两端迭代时不会越过对方
+        if self.len == 0 {
+            return None;
+        }
+        // SAFETY: 剩余的节点都在被 &'a 借用的链表中
+        self.head.map(|node| unsafe {
+            self.len -= 1;
+            self.head = (*node.as_ptr()).next;
+            &(*node.as_ptr()).value
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        // SAFETY: 同 next，len 保证不会越过 next 已经返回过的节点
+        self.tail.map(|node| unsafe {
+            self.len -= 1;
+            self.tail = (*node.as_ptr()).prev;
+            &(*node.as_ptr()).value
+        })
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<T> Clone for Iter<'_, T> {
+    fn clone(&self) -> Self {
+        Iter { ..*self }
+    }
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        // SAFETY: 链表被 &'a mut 借用，每个节点只会被返回一次，返回的可变引用互不重叠
+        self.head.map(|node| unsafe {
+            self.len -= 1;
+            self.head = (*node.as_ptr()).next;
+            &mut (*node.as_ptr()).value
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IterMut<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        // SAFETY: 同 next，len 保证两端返回的节点不会重复，可变引用不会重叠
+        self.tail.map(|node| unsafe {
+            self.len -= 1;
+            self.tail = (*node.as_ptr()).prev;
+            &mut (*node.as_ptr()).value
+        })
+    }
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.list.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.list.pop_back()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+impl<T> Iterator for Drain<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.list.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for Drain<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.list.pop_back()
+    }
+}
+
+impl<T> ExactSizeIterator for Drain<'_, T> {}
+
+impl<T> IntoIterator for List<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter { list: self }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a List<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut List<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> IterMut<'a, T> {
+        self.iter_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::linked_list::rng::Rng;
+    use std::cell::Cell;
+    use std::collections::hash_map::DefaultHasher;
+    use std::collections::VecDeque;
+
+    fn hash<T: Hash>(value: &T) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn assert_matches(list: &List<i32>, expected: &VecDeque<i32>) {
+        assert_eq!(list.len(), expected.len());
+        assert!(list.iter().eq(expected));
+        assert!(list.iter().rev().eq(expected.iter().rev()));
+        assert_eq!(list.front(), expected.front());
+        assert_eq!(list.back(), expected.back());
+    }
+
+    #[test]
+    fn test_basics() {
+        let mut list = List::new();
+        assert_eq!(list.pop_front(), None);
+        assert_eq!(list.pop_back(), None);
+
+        list.push_front(2);
+        list.push_front(1);
+        list.push_back(3);
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.pop_back(), Some(3));
+        assert_eq!(list.pop_front(), Some(1));
+        assert_eq!(list.pop_front(), Some(2));
+        assert_eq!(list.pop_back(), None);
+        assert!(list.is_empty());
+
+        list.push_back(4);
+        assert_eq!(list.pop_front(), Some(4));
+        list.push_front(5);
+        assert_eq!(list.pop_back(), Some(5));
+        assert!(list.front().is_none() && list.back().is_none());
+    }
+
+    #[test]
+    fn test_peek() {
+        let mut list: List<i32> = (1..=3).collect();
+        assert_eq!(list.front(), Some(&1));
+        assert_eq!(list.back(), Some(&3));
+        *list.front_mut().unwrap() = 10;
+        *list.back_mut().unwrap() *= 10;
+        assert_eq!(list.get(0), Some(&10));
+        assert_eq!(list.get(2), Some(&30));
+        assert_eq!(list.get(3), None);
+
+        *list.get_mut(1).unwrap() = 20;
+        assert!(list.contains(&20) && !list.contains(&2));
+        assert_eq!(list, [10, 20, 30].into_iter().collect());
+
+        let list: List<usize> = (0..7).collect();
+        for i in 0..7 {
+            assert_eq!(list.get(i), Some(&i));
+        }
+    }
+
+    #[test]
+    fn test_iter() {
+        let mut list: List<i32> = (1..=5).collect();
+        let mut iter = list.iter();
+        assert_eq!(iter.len(), 5);
+        assert_eq!(iter.next(), Some(&1));
+        assert_eq!(iter.next_back(), Some(&5));
+        let rest = iter.clone();
+        assert_eq!(iter.next(), Some(&2));
+        assert_eq!(iter.next_back(), Some(&4));
+        assert_eq!(iter.next(), Some(&3));
+        assert_eq!(iter.next_back(), None);
+        assert_eq!(iter.next(), None);
+        assert_eq!(rest.collect::<Vec<_>>(), vec![&2, &3, &4]);
+
+        for value in &mut list {
+            *value *= 10;
+        }
+        let mut iter = list.iter_mut();
+        *iter.next_back().unwrap() += 1;
+        *iter.next().unwrap() += 2;
+        assert_eq!(iter.len(), 3);
+        assert_eq!(
+            list.iter().copied().collect::<Vec<_>>(),
+            vec![12, 20, 30, 40, 51]
+        );
+
+        let mut iter = list.into_iter();
+        assert_eq!(iter.next_back(), Some(51));
+        assert_eq!(iter.next(), Some(12));
+        assert_eq!(iter.len(), 3);
+        assert_eq!(iter.rev().collect::<Vec<_>>(), vec![40, 30, 20]);
+    }
+
+    #[test]
+    fn test_append_split_off() {
+        let mut list: List<i32> = (1..=3).collect();
+        let mut other: List<i32> = (4..=6).collect();
+        list.append(&mut other);
+        assert!(other.is_empty());
+        assert_eq!(
+            list.iter().rev().copied().collect::<Vec<_>>(),
+            vec![6, 5, 4, 3, 2, 1]
+        );
+
+        // 追加到空链表以及追加空链表
+        other.append(&mut list);
+        other.append(&mut List::new());
+        assert!(list.is_empty());
+        assert_eq!(other.len(), 6);
+
+        let mut tail = other.split_off(4);
+        assert_eq!(tail, (5..=6).collect());
+        assert_eq!(other, (1..=4).collect());
+        assert_eq!(other.back(), Some(&4));
+        assert!(other.split_off(4).is_empty());
+        let all = other.split_off(0);
+        assert!(other.is_empty() && other.front().is_none());
+        assert_eq!(all, (1..=4).collect());
+
+        tail.push_front(0);
+        tail.push_back(7);
+        assert_eq!(
+            tail.iter().rev().copied().collect::<Vec<_>>(),
+            vec![7, 6, 5, 0]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "nonexistent index")]
+    fn test_split_off_out_of_bounds() {
+        let mut list: List<i32> = (1..=3).collect();
+        list.split_off(4);
+    }
+
+    #[test]
+    fn test_retain() {
+        let mut list: List<i32> = (1..=10).collect();
+        list.retain(|v| v % 3 != 1);
+        assert_eq!(list, [2, 3, 5, 6, 8, 9].into_iter().collect());
+        assert_eq!((list.front(), list.back()), (Some(&2), Some(&9)));
+        list.retain(|_| false);
+        assert!(list.is_empty() && list.front().is_none() && list.back().is_none());
+    }
+
+    #[test]
+    fn test_insert_remove() {
+        let mut list: List<i32> = List::new();
+        list.insert(0, 2);
+        list.insert(0, 0);
+        list.insert(1, 1);
+        list.insert(3, 4);
+        list.insert(3, 3);
+        assert_eq!(list, (0..=4).collect());
+        assert_eq!((list.front(), list.back()), (Some(&0), Some(&4)));
+
+        assert_eq!(list.remove(5), None);
+        assert_eq!(list.remove(2), Some(2));
+        assert_eq!(list.remove(0), Some(0));
+        assert_eq!(list.remove(2), Some(4));
+        assert_eq!(list, [1, 3].into_iter().collect());
+        assert_eq!(list.back(), Some(&3));
+
+        list.truncate(5);
+        assert_eq!(list.len(), 2);
+        list.truncate(1);
+        assert_eq!((list.front(), list.back()), (Some(&1), Some(&1)));
+        list.truncate(0);
+        assert!(list.is_empty() && list.back().is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "index out of bounds")]
+    fn test_insert_out_of_bounds() {
+        let mut list: List<i32> = (1..=3).collect();
+        list.insert(4, 0);
+    }
+
+    #[test]
+    fn test_swap_rotate() {
+        let mut list: List<i32> = (0..5).collect();
+        list.swap(0, 4);
+        list.swap(1, 1);
+        list.swap(3, 2);
+        assert_eq!(list, [4, 1, 3, 2, 0].into_iter().collect());
+
+        let mut list: List<i32> = (0..5).collect();
+        list.rotate_left(2);
+        assert_eq!(list, [2, 3, 4, 0, 1].into_iter().collect());
+        assert_eq!((list.front(), list.back()), (Some(&2), Some(&1)));
+        list.rotate_right(2);
+        assert_eq!(list, (0..5).collect());
+        list.rotate_left(0);
+        list.rotate_right(5);
+        assert_eq!(list, (0..5).collect());
+        list.rotate_right(1);
+        assert!(list.iter().rev().eq(&[3, 2, 1, 0, 4]));
+
+        let mut empty: List<i32> = List::new();
+        empty.rotate_left(0);
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "rotation amount out of bounds")]
+    fn test_rotate_out_of_bounds() {
+        let mut list: List<i32> = (1..=3).collect();
+        list.rotate_left(4);
+    }
+
+    #[test]
+    #[should_panic(expected = "index out of bounds")]
+    fn test_swap_out_of_bounds() {
+        let mut list: List<i32> = (1..=3).collect();
+        list.swap(0, 3);
+    }
+
+    #[test]
+    fn test_drain() {
+        let mut list: List<i32> = (0..6).collect();
+        let mut drain = list.drain(1..4);
+        assert_eq!(drain.len(), 3);
+        assert_eq!(drain.next_back(), Some(3));
+        assert_eq!(drain.next(), Some(1));
+        drop(drain);
+        assert_eq!(list, [0, 4, 5].into_iter().collect());
+
+        assert_eq!(list.drain(..=1).collect::<Vec<_>>(), vec![0, 4]);
+        assert_eq!(list.drain(1..).count(), 0);
+        assert_eq!(list.drain(..).collect::<Vec<_>>(), vec![5]);
+        assert!(list.is_empty() && list.front().is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "drain range out of bounds")]
+    fn test_drain_out_of_bounds() {
+        let mut list: List<i32> = (1..=3).collect();
+        list.drain(2..4);
+    }
+
+    #[test]
+    fn test_traits() {
+        let list: List<i32> = (1..=3).collect();
+        let copy = list.clone();
+        assert_eq!(list, copy);
+        assert_eq!(hash(&list), hash(&copy));
+        assert_eq!(format!("{:?}", list), "[1, 2, 3]");
+        assert_eq!(List::<i32>::default(), List::new());
+
+        let shorter: List<i32> = (1..=2).collect();
+        let bigger: List<i32> = [1, 3].into_iter().collect();
+        assert!(shorter < list && list < bigger);
+        assert_eq!(list.cmp(&copy), Ordering::Equal);
+
+        let nested: List<List<i32>> = [(1..=1).collect(), (2..=2).collect()].into_iter().collect();
+        let flat: List<List<i32>> = [(1..=2).collect()].into_iter().collect();
+        assert_ne!(hash(&nested), hash(&flat));
+
+        let mut list = list;
+        list.extend(4..=5);
+        assert_eq!(list.len(), 5);
+    }
+
+    #[test]
+    fn test_drop() {
+        struct DropCounter<'a>(&'a Cell<usize>);
+
+        impl Drop for DropCounter<'_> {
+            fn drop(&mut self) {
+                self.0.set(self.0.get() + 1);
+            }
+        }
+
+        let drops = Cell::new(0);
+        let mut list: List<DropCounter> = (0..5).map(|_| DropCounter(&drops)).collect();
+        drop(list.pop_front());
+        assert_eq!(drops.get(), 1);
+        let rest = list.split_off(2);
+        drop(list);
+        assert_eq!(drops.get(), 3);
+
+        let mut iter = rest.into_iter();
+        iter.next();
+        drop(iter);
+        assert_eq!(drops.get(), 5);
+
+        // drain 没有迭代完的元素在 Drain 被 drop 时释放，remove 和 truncate 也会释放元素
+        let mut list: List<DropCounter> = (0..6).map(|_| DropCounter(&drops)).collect();
+        list.drain(1..4).next();
+        assert_eq!(drops.get(), 8);
+        list.remove(0);
+        list.truncate(1);
+        assert_eq!(drops.get(), 10);
+        drop(list);
+        assert_eq!(drops.get(), 11);
+    }
+
+    // 与 VecDeque 在同一串随机操作下逐步比较
+    #[test]
+    fn test_against_vec_deque() {
+        let mut list = List::new();
+        let mut expected = VecDeque::new();
+        let mut rng = Rng::new();
+        let rounds = if cfg!(miri) { 200 } else { 5000 };
+        for _ in 0..rounds {
+            let value = (rng.next_u64() % 100) as i32;
+            match rng.next_u64() % 14 {
+                0 | 1 => {
+                    list.push_front(value);
+                    expected.push_front(value);
+                }
+                2 | 3 => {
+                    list.push_back(value);
+                    expected.push_back(value);
+                }
+                4 => assert_eq!(list.pop_front(), expected.pop_front()),
+                5 => assert_eq!(list.pop_back(), expected.pop_back()),
+                6 => {
+                    let at = rng.next_u64() as usize % (expected.len() + 1);
+                    let mut tail = list.split_off(at);
+                    let expected_tail = expected.split_off(at);
+                    assert_matches(&tail, &expected_tail);
+                    list.append(&mut tail);
+                    expected.extend(expected_tail);
+                }
+                7 => {
+                    let index = rng.next_u64() as usize % (expected.len() + 1);
+                    list.insert(index, value);
+                    expected.insert(index, value);
+                }
+                8 => {
+                    let index = rng.next_u64() as usize % (expected.len() + 1);
+                    assert_eq!(list.remove(index), expected.remove(index));
+                }
+                9 if !expected.is_empty() => {
+                    let i = rng.next_u64() as usize % expected.len();
+                    let j = rng.next_u64() as usize % expected.len();
+                    list.swap(i, j);
+                    expected.swap(i, j);
+                }
+                10 => {
+                    let n = rng.next_u64() as usize % (expected.len() + 1);
+                    list.rotate_left(n);
+                    expected.rotate_left(n);
+                }
+                11 => {
+                    let n = rng.next_u64() as usize % (expected.len() + 1);
+                    list.rotate_right(n);
+                    expected.rotate_right(n);
+                }
+                12 => {
+                    let end = rng.next_u64() as usize % (expected.len() + 1);
+                    let start = rng.next_u64() as usize % (end + 1);
+                    assert!(list.drain(start..end).eq(expected.drain(start..end)));
+                }
+                13 => {
+                    let len = expected.len().saturating_sub(rng.next_u64() as usize % 3);
+                    list.truncate(len);
+                    expected.truncate(len);
+                }
+                _ => {
+                    let index = rng.next_u64() as usize % (expected.len() + 1);
+                    if let Some(v) = list.get_mut(index) {
+                        *v = value;
+                    }
+                    if let Some(v) = expected.get_mut(index) {
+                        *v = value;
+                    }
+                }
+            }
+            assert_matches(&list, &expected);
+        }
+    }
+
+    // 只要能编译通过就说明 List 和 Iter 对 T 是协变的，并且满足 Send 和 Sync
+    #[test]
+    fn test_variance_and_auto_traits() {
+        fn list_covariant<'a>(list: List<&'static str>) -> List<&'a str> {
+            list
+        }
+        fn iter_covariant<'i, 'a>(iter: Iter<'i, &'static str>) -> Iter<'i, &'a str> {
+            iter
+        }
+        fn into_iter_covariant<'a>(iter: IntoIter<&'static str>) -> IntoIter<&'a str> {
+            iter
+        }
+        fn is_send<T: Send>() {}
+        fn is_sync<T: Sync>() {}
+
+        let list: List<&str> = ["a"].into_iter().collect();
+        assert_eq!(iter_covariant(list.iter()).next(), Some(&"a"));
+        assert_eq!(list_covariant(list.clone()), list);
+        assert_eq!(into_iter_covariant(list.into_iter()).next(), Some("a"));
+
+        is_send::<List<i32>>();
+        is_sync::<List<i32>>();
+        is_send::<Iter<i32>>();
+        is_sync::<Iter<i32>>();
+        is_send::<IterMut<i32>>();
+        is_sync::<IterMut<i32>>();
+        is_send::<IntoIter<i32>>();
+        is_sync::<IntoIter<i32>>();
+
+        let list: List<i32> = (1..=3).collect();
+        let sum = std::thread::spawn(move || list.iter().sum::<i32>())
+            .join()
+            .unwrap();
+        assert_eq!(sum, 6);
+    }
+}
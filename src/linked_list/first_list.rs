@@ -12,6 +12,12 @@ pub struct List {
     head: Link,
 }
 
+impl Default for List {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl List {
     pub fn new() -> Self {
         List { head: Link::Empty }
@@ -35,6 +41,13 @@ impl List {
             }
         }
     }
+
+    pub fn peek(&self) -> Option<&i32> {
+        match &self.head {
+            Link::Empty => None,
+            Link::More(node) => Some(&node.value),
+        }
+    }
 }
 
 impl Drop for List {
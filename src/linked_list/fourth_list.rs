@@ -1,7 +1,8 @@
 /// 双向链表
 use std::{
     cell::{Ref, RefCell, RefMut},
-    rc::Rc,
+    mem,
+    rc::{Rc, Weak},
 };
 
 type Link<T> = Option<Rc<RefCell<Node<T>>>>;
@@ -16,6 +17,10 @@ struct Node<T> {
 pub struct List<T> {
     head: Link<T>,
     tail: Link<T>,
+    // 记录长度，这样游标拆分链表时可以在 O(1) 时间内得到两部分的长度
+    len: usize,
+    // 链表的身份标识，句柄中记录创建它的链表的 id，用来拒绝其他链表的句柄
+    id: Rc<()>,
 }
 
 impl<T> Node<T> {
@@ -28,14 +33,30 @@ impl<T> Node<T> {
     }
 }
 
+impl<T> Default for List<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<T> List<T> {
     pub fn new() -> Self {
         List {
             head: None,
             tail: None,
+            len: 0,
+            id: Rc::new(()),
         }
     }
 
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
     pub fn push_front(&mut self, value: T) {
         let new_node = Node::new(value);
         match self.head.take() {
@@ -52,10 +73,57 @@ impl<T> List<T> {
                 self.tail = Some(new_node);
             }
         }
+        self.len += 1;
     }
 
     pub fn push_back(&mut self, value: T) {
+        self.link_back(Node::new(value));
+    }
+
+    /// 与 push_back 相同，同时返回指向新节点的句柄
+    pub fn push_back_handle(&mut self, value: T) -> Handle<T> {
         let new_node = Node::new(value);
+        let handle = Handle {
+            node: Rc::downgrade(&new_node),
+            list: Rc::downgrade(&self.id),
+        };
+        self.link_back(new_node);
+        handle
+    }
+
+    /// 删除句柄指向的节点并返回其中的值，O(1)。
+    /// 节点已经不在链表中，或者句柄来自其他链表时返回 None
+    pub fn remove(&mut self, handle: &Handle<T>) -> Option<T> {
+        let node = self.node_of(handle)?;
+        self.unlink(&node);
+        // 链表和相邻节点都不再持有这个节点，句柄只是 Weak 引用，引用计数为 1
+        let node = Rc::try_unwrap(node)
+            .ok()
+            .expect("a node unlinked from the list should have no other strong references");
+        Some(node.into_inner().value)
+    }
+
+    /// 把句柄指向的节点移动到链表的尾部，O(1)，不重新分配节点，句柄仍然有效。
+    /// 节点已经不在链表中，或者句柄来自其他链表时返回 false
+    pub fn move_to_back(&mut self, handle: &Handle<T>) -> bool {
+        let Some(node) = self.node_of(handle) else {
+            return false;
+        };
+        self.unlink(&node);
+        self.link_back(node);
+        true
+    }
+
+    // 句柄来自这个链表并且节点还没有被释放时返回节点。
+    // 句柄持有原链表 id 的 Weak 引用，原链表释放之后这块内存也不会被新的链表复用，比较地址即可
+    fn node_of(&self, handle: &Handle<T>) -> Option<Rc<RefCell<Node<T>>>> {
+        if handle.list.as_ptr() != Rc::as_ptr(&self.id) {
+            return None;
+        }
+        handle.node.upgrade()
+    }
+
+    fn link_back(&mut self, new_node: Rc<RefCell<Node<T>>>) {
         match self.tail.take() {
             Some(old_tail) => {
                 old_tail.borrow_mut().next = Some(new_node.clone());
@@ -67,6 +135,23 @@ impl<T> List<T> {
                 self.tail = Some(new_node);
             }
         }
+        self.len += 1;
+    }
+
+    // 把 node 从链表中摘下，返回它原来的下一个节点
+    fn unlink(&mut self, node: &Rc<RefCell<Node<T>>>) -> Link<T> {
+        let prev = node.borrow_mut().prev.take();
+        let next = node.borrow_mut().next.take();
+        match &prev {
+            Some(prev) => prev.borrow_mut().next = next.clone(),
+            None => self.head = next.clone(),
+        }
+        match &next {
+            Some(next) => next.borrow_mut().prev = prev,
+            None => self.tail = prev,
+        }
+        self.len -= 1;
+        next
     }
 
     pub fn pop_front(&mut self) -> Option<T> {
@@ -83,6 +168,7 @@ impl<T> List<T> {
                     self.tail.take();
                 }
             }
+            self.len -= 1;
             // into_inner() 消费掉 RefCell 并返回内部的值
             Rc::try_unwrap(old_head).ok().unwrap().into_inner().value
         })
@@ -99,11 +185,12 @@ impl<T> List<T> {
                     self.head.take();
                 }
             }
+            self.len -= 1;
             Rc::try_unwrap(old_tail).ok().unwrap().into_inner().value
         })
     }
 
-    pub fn peek_front(&self) -> Option<Ref<T>> {
+    pub fn peek_front(&self) -> Option<Ref<'_, T>> {
         // map<U, F>(orig: Ref<'b, T>, f: F) -> Ref<'b, U>
         // where F: FnOnce(&T) -> &U,
         // U: ?Sized
@@ -113,13 +200,13 @@ impl<T> List<T> {
             .map(|node| Ref::map(node.borrow(), |node| &node.value))
     }
 
-    pub fn peek_back(&self) -> Option<Ref<T>> {
+    pub fn peek_back(&self) -> Option<Ref<'_, T>> {
         self.tail
             .as_ref()
             .map(|node| Ref::map(node.borrow(), |node| &node.value))
     }
 
-    pub fn peek_front_mut(&mut self) -> Option<RefMut<T>> {
+    pub fn peek_front_mut(&mut self) -> Option<RefMut<'_, T>> {
         // map<U, F>(orig: Ref<'b, T>, f: F) -> Ref<'b, U>
         // where F: FnOnce(&T) -> &U,
         // U: ?Sized
@@ -129,13 +216,100 @@ impl<T> List<T> {
             .map(|node| RefMut::map(node.borrow_mut(), |node| &mut node.value))
     }
 
-    pub fn peek_back_mut(&mut self) -> Option<RefMut<T>> {
+    pub fn peek_back_mut(&mut self) -> Option<RefMut<'_, T>> {
         self.tail
             .as_ref()
             .map(|node| RefMut::map(node.borrow_mut(), |node| &mut node.value))
     }
 
-    pub fn into_iter(self) -> IntoIter<T> {
+    // 节点放在 RefCell 中，下一个节点只能通过当前节点的 Ref 拿到，返回 Ref<T> 的迭代器
+    // 做不到让下一个 Ref 比当前的 Ref 活得更久，所以不消耗链表的遍历用闭包的形式提供：
+    // 每个节点只在调用 f 期间被借用，调用结束后再沿着链接走到下一个节点
+
+    /// 从头到尾依次用每个元素调用 f
+    pub fn for_each(&self, mut f: impl FnMut(&T)) {
+        Self::walk(
+            self.head.clone(),
+            |node| node.next.clone(),
+            |node| f(&node.borrow().value),
+        );
+    }
+
+    /// 从尾到头依次用每个元素调用 f
+    pub fn for_each_back(&self, mut f: impl FnMut(&T)) {
+        Self::walk(
+            self.tail.clone(),
+            |node| node.prev.clone(),
+            |node| f(&node.borrow().value),
+        );
+    }
+
+    /// 从头到尾依次用每个元素的可变引用调用 f，借用了 &mut self，f 中无法再借用链表，不会出现重复借用
+    pub fn for_each_mut(&mut self, mut f: impl FnMut(&mut T)) {
+        Self::walk(
+            self.head.clone(),
+            |node| node.next.clone(),
+            |node| f(&mut node.borrow_mut().value),
+        );
+    }
+
+    /// 从尾到头依次用每个元素的可变引用调用 f
+    pub fn for_each_back_mut(&mut self, mut f: impl FnMut(&mut T)) {
+        Self::walk(
+            self.tail.clone(),
+            |node| node.prev.clone(),
+            |node| f(&mut node.borrow_mut().value),
+        );
+    }
+
+    fn walk(
+        mut link: Link<T>,
+        step: impl Fn(&Node<T>) -> Link<T>,
+        mut visit: impl FnMut(&Rc<RefCell<Node<T>>>),
+    ) {
+        while let Some(node) = link {
+            visit(&node);
+            link = step(&node.borrow());
+        }
+    }
+
+    /// 指向头节点的游标，链表为空时指向幽灵位置
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            current: self.head.clone(),
+            index: self.head.as_ref().map(|_| 0),
+            list: self,
+        }
+    }
+
+    /// 指向尾节点的游标，链表为空时指向幽灵位置
+    pub fn cursor_back_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            current: self.tail.clone(),
+            index: self.len.checked_sub(1),
+            list: self,
+        }
+    }
+
+    // 游标拆分链表时，用已经从链表上断开的一段节点组成新的链表，并从原链表的长度中减去这一段的长度。
+    // 一部分节点离开了链表，原链表换一个 id 让之前发出的句柄全部失效
+    fn detach(&mut self, head: Link<T>, tail: Link<T>, len: usize) -> List<T> {
+        self.len -= len;
+        self.id = Rc::new(());
+        List {
+            head,
+            tail,
+            len,
+            id: Rc::new(()),
+        }
+    }
+}
+
+impl<T> IntoIterator for List<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
         IntoIter(self)
     }
 }
@@ -156,6 +330,226 @@ impl<T> DoubleEndedIterator for IntoIter<T> {
     }
 }
 
+/// 指向链表中某个节点的句柄，可以在 O(1) 时间内删除或者移动这个节点。
+/// 句柄只持有节点的 Weak 引用，不会妨碍节点被弹出（pop 中的 Rc::try_unwrap 不会因此失败），
+/// 节点离开链表被释放之后，句柄随之失效。
+/// 句柄只对创建它的链表有效，用在其他链表上时 remove 和 move_to_back 什么都不做；
+/// 游标拆分链表时节点会移动到新的链表中，原链表之前发出的句柄也会全部失效
+pub struct Handle<T> {
+    node: Weak<RefCell<Node<T>>>,
+    list: Weak<()>,
+}
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        Handle {
+            node: self.node.clone(),
+            list: self.list.clone(),
+        }
+    }
+}
+
+/// 可以在链表中前后移动，并在当前位置插入、删除、拆分和拼接的游标。
+/// 除了链表中的节点，游标还可以指向尾节点和头节点之间的“幽灵”位置：从幽灵位置向后移动到头节点，
+/// 向前移动到尾节点，就像链表首尾相连成了一个环，这样在链表的两端也可以进行同样的操作
+pub struct CursorMut<'a, T> {
+    list: &'a mut List<T>,
+    // None 表示幽灵位置
+    current: Link<T>,
+    index: Option<usize>,
+}
+
+impl<T> CursorMut<'_, T> {
+    /// 当前节点在链表中的位置，幽灵位置返回 None
+    pub fn index(&self) -> Option<usize> {
+        self.index
+    }
+
+    pub fn current(&mut self) -> Option<RefMut<'_, T>> {
+        self.current
+            .as_ref()
+            .map(|node| RefMut::map(node.borrow_mut(), |node| &mut node.value))
+    }
+
+    pub fn move_next(&mut self) {
+        match self.current.take() {
+            Some(node) => {
+                self.current = node.borrow().next.clone();
+                self.index = match self.current {
+                    Some(_) => self.index.map(|i| i + 1),
+                    None => None,
+                };
+            }
+            None => {
+                self.current = self.list.head.clone();
+                self.index = self.current.as_ref().map(|_| 0);
+            }
+        }
+    }
+
+    pub fn move_prev(&mut self) {
+        match self.current.take() {
+            Some(node) => {
+                self.current = node.borrow().prev.clone();
+                self.index = match self.current {
+                    Some(_) => self.index.map(|i| i - 1),
+                    None => None,
+                };
+            }
+            None => {
+                self.current = self.list.tail.clone();
+                self.index = self.list.len.checked_sub(1);
+            }
+        }
+    }
+
+    /// 在当前节点之后插入，位于幽灵位置时插入到链表的头部
+    pub fn insert_after(&mut self, value: T) {
+        let Some(current) = &self.current else {
+            self.list.push_front(value);
+            return;
+        };
+
+        let new_node = Node::new(value);
+        let next = current.borrow_mut().next.take();
+        match next {
+            Some(next) => {
+                next.borrow_mut().prev = Some(new_node.clone());
+                new_node.borrow_mut().next = Some(next);
+            }
+            None => self.list.tail = Some(new_node.clone()),
+        }
+        new_node.borrow_mut().prev = Some(current.clone());
+        current.borrow_mut().next = Some(new_node);
+        self.list.len += 1;
+    }
+
+    /// 在当前节点之前插入，位于幽灵位置时插入到链表的尾部
+    pub fn insert_before(&mut self, value: T) {
+        let Some(current) = &self.current else {
+            self.list.push_back(value);
+            return;
+        };
+
+        let new_node = Node::new(value);
+        let prev = current.borrow_mut().prev.take();
+        match prev {
+            Some(prev) => {
+                prev.borrow_mut().next = Some(new_node.clone());
+                new_node.borrow_mut().prev = Some(prev);
+            }
+            None => self.list.head = Some(new_node.clone()),
+        }
+        new_node.borrow_mut().next = Some(current.clone());
+        current.borrow_mut().prev = Some(new_node);
+        self.list.len += 1;
+        self.index = self.index.map(|i| i + 1);
+    }
+
+    /// 删除并返回当前节点的值，游标移动到下一个节点（删除的是尾节点时移动到幽灵位置）
+    pub fn remove_current(&mut self) -> Option<T> {
+        let node = self.current.take()?;
+        let next = self.list.unlink(&node);
+        if next.is_none() {
+            self.index = None;
+        }
+        self.current = next;
+        // 相邻节点、链表和游标都不再持有这个节点，引用计数为 1
+        let node = Rc::try_unwrap(node)
+            .ok()
+            .expect("a node unlinked from the list should have no other strong references");
+        Some(node.into_inner().value)
+    }
+
+    /// 把当前节点之后的部分拆分成一个新的链表返回，位于幽灵位置时返回整个链表
+    pub fn split_after(&mut self) -> List<T> {
+        let Some(current) = &self.current else {
+            return mem::take(self.list);
+        };
+
+        let index = self.index.unwrap();
+        let Some(next) = current.borrow_mut().next.take() else {
+            return List::new();
+        };
+        next.borrow_mut().prev = None;
+        let tail = self.list.tail.replace(current.clone());
+        let len = self.list.len - index - 1;
+        self.list.detach(Some(next), tail, len)
+    }
+
+    /// 把当前节点之前的部分拆分成一个新的链表返回，位于幽灵位置时返回整个链表
+    pub fn split_before(&mut self) -> List<T> {
+        let Some(current) = &self.current else {
+            return mem::take(self.list);
+        };
+
+        let index = self.index.unwrap();
+        let Some(prev) = current.borrow_mut().prev.take() else {
+            return List::new();
+        };
+        prev.borrow_mut().next = None;
+        let head = self.list.head.replace(current.clone());
+        self.index = Some(0);
+        self.list.detach(head, Some(prev), index)
+    }
+
+    /// 把 other 整个拼接到当前节点之后，位于幽灵位置时拼接到链表的头部
+    pub fn splice_after(&mut self, mut other: List<T>) {
+        let (Some(other_head), Some(other_tail)) = (other.head.take(), other.tail.take()) else {
+            return;
+        };
+        // 节点都已经取出，other 被 drop 时不会再释放它们
+        let other_len = mem::take(&mut other.len);
+
+        let next = match &self.current {
+            Some(current) => {
+                other_head.borrow_mut().prev = Some(current.clone());
+                current.borrow_mut().next.replace(other_head)
+            }
+            None => self.list.head.replace(other_head),
+        };
+        match next {
+            Some(next) => {
+                next.borrow_mut().prev = Some(other_tail.clone());
+                other_tail.borrow_mut().next = Some(next);
+            }
+            None => self.list.tail = Some(other_tail),
+        }
+        self.list.len += other_len;
+    }
+
+    /// 把 other 整个拼接到当前节点之前，位于幽灵位置时拼接到链表的尾部
+    pub fn splice_before(&mut self, mut other: List<T>) {
+        let (Some(other_head), Some(other_tail)) = (other.head.take(), other.tail.take()) else {
+            return;
+        };
+        let other_len = mem::take(&mut other.len);
+
+        let prev = match &self.current {
+            Some(current) => {
+                other_tail.borrow_mut().next = Some(current.clone());
+                current.borrow_mut().prev.replace(other_tail)
+            }
+            None => self.list.tail.replace(other_tail),
+        };
+        match prev {
+            Some(prev) => {
+                prev.borrow_mut().next = Some(other_head.clone());
+                other_head.borrow_mut().prev = Some(prev);
+            }
+            None => self.list.head = Some(other_head),
+        }
+        self.list.len += other_len;
+        self.index = self.index.map(|i| i + other_len);
+    }
+}
+
+// 游标持有当前节点的 Rc，如果游标比它对链表的借用活得更久，链表弹出节点时 Rc::try_unwrap 就会失败。
+// 实现 Drop 之后，借用检查器会认为游标在被释放之前一直借用着链表，在编译期就排除了这种情况
+impl<T> Drop for CursorMut<'_, T> {
+    fn drop(&mut self) {}
+}
+
 // 不实现Drop的情况下，默认的drop只是每次将引用计数减1，若存在循环引用则可能出现引用计数不能清零的情况
 impl<T> Drop for List<T> {
     fn drop(&mut self) {
@@ -166,6 +560,45 @@ impl<T> Drop for List<T> {
 #[cfg(test)]
 mod tests {
     use super::List;
+    use std::mem;
+
+    fn from(values: &[i32]) -> List<i32> {
+        let mut list = List::new();
+        for &value in values {
+            list.push_back(value);
+        }
+        list
+    }
+
+    // 用游标分别从两个方向遍历，检查 next 和 prev 两个方向的链接、游标的位置以及长度都是一致的
+    fn contents(list: &mut List<i32>) -> Vec<i32> {
+        let len = list.len();
+        let mut forward = Vec::new();
+        {
+            let mut cursor = list.cursor_front_mut();
+            while let Some(value) = cursor.current().map(|value| *value) {
+                assert_eq!(cursor.index(), Some(forward.len()));
+                forward.push(value);
+                cursor.move_next();
+            }
+            assert_eq!(cursor.index(), None);
+        }
+
+        let mut backward = Vec::new();
+        {
+            let mut cursor = list.cursor_back_mut();
+            while let Some(value) = cursor.current().map(|value| *value) {
+                assert_eq!(cursor.index(), Some(len - 1 - backward.len()));
+                backward.push(value);
+                cursor.move_prev();
+            }
+        }
+        backward.reverse();
+
+        assert_eq!(forward, backward);
+        assert_eq!(forward.len(), len);
+        forward
+    }
 
     #[test]
     pub fn test_basics() {
@@ -245,4 +678,306 @@ mod tests {
         assert_eq!(iter.next_back(), None);
         assert_eq!(iter.next(), None);
     }
+
+    #[test]
+    fn test_for_each() {
+        let mut list = from(&[1, 2, 3]);
+        let mut values = Vec::new();
+        list.for_each(|&v| values.push(v));
+        assert_eq!(values, vec![1, 2, 3]);
+
+        let mut values = Vec::new();
+        list.for_each_back(|&v| values.push(v));
+        assert_eq!(values, vec![3, 2, 1]);
+
+        // 遍历期间仍然可以通过共享引用查看链表
+        let mut sum = 0;
+        list.for_each(|v| sum += v * *list.peek_back().unwrap());
+        assert_eq!(sum, 18);
+
+        list.for_each_mut(|v| *v *= 10);
+        let mut next = 0;
+        list.for_each_back_mut(|v| {
+            next += 1;
+            *v += next;
+        });
+        assert_eq!(contents(&mut list), vec![13, 22, 31]);
+
+        let empty: List<i32> = List::new();
+        empty.for_each(|_| unreachable!());
+        empty.for_each_back(|_| unreachable!());
+    }
+
+    #[test]
+    fn test_handle() {
+        let mut list = List::new();
+        let one = list.push_back_handle(1);
+        let two = list.push_back_handle(2);
+        let three = list.push_back_handle(3);
+
+        assert!(list.move_to_back(&one));
+        assert_eq!(contents(&mut list), vec![2, 3, 1]);
+        assert!(list.move_to_back(&one));
+        assert_eq!(contents(&mut list), vec![2, 3, 1]);
+
+        assert_eq!(list.remove(&three), Some(3));
+        assert_eq!(list.remove(&three.clone()), None);
+        assert!(!list.move_to_back(&three));
+        assert_eq!(contents(&mut list), vec![2, 1]);
+
+        // 句柄不会妨碍弹出节点，弹出之后句柄失效
+        assert_eq!(list.pop_front(), Some(2));
+        assert_eq!(list.remove(&two), None);
+        assert_eq!(list.remove(&one), Some(1));
+        assert!(list.is_empty() && list.peek_front().is_none() && list.peek_back().is_none());
+    }
+
+    #[test]
+    fn test_handle_from_another_list() {
+        let mut a = List::new();
+        let only = a.push_back_handle(1);
+        let mut b = from(&[2, 3]);
+        let mut empty: List<i32> = List::new();
+
+        // 其他链表的句柄不会修改这个链表，也不会影响句柄所属的链表
+        assert_eq!(b.remove(&only), None);
+        assert!(!b.move_to_back(&only));
+        assert_eq!(empty.remove(&only), None);
+        assert_eq!(contents(&mut b), vec![2, 3]);
+        assert!(empty.is_empty());
+        assert_eq!(contents(&mut a), vec![1]);
+
+        // 整个链表被移动时句柄跟着一起走
+        let mut moved = mem::take(&mut a);
+        assert_eq!(a.remove(&only), None);
+        assert_eq!(moved.remove(&only), Some(1));
+
+        // 拆分之后，原链表之前发出的句柄都失效了，包括留在原链表中的节点
+        let mut list = List::new();
+        let first = list.push_back_handle(1);
+        let second = list.push_back_handle(2);
+        let mut rest = {
+            let mut cursor = list.cursor_front_mut();
+            cursor.split_after()
+        };
+        assert!(!list.move_to_back(&first));
+        assert_eq!(rest.remove(&second), None);
+        assert_eq!(list.len() + rest.len(), 2);
+    }
+
+    #[test]
+    fn test_cursor_move() {
+        // 空链表的游标只有幽灵位置，怎么移动都停在原地
+        let mut list: List<i32> = List::new();
+        let mut cursor = list.cursor_front_mut();
+        assert_eq!(cursor.index(), None);
+        assert!(cursor.current().is_none());
+        cursor.move_next();
+        cursor.move_prev();
+        assert!(cursor.current().is_none());
+        assert_eq!(cursor.remove_current(), None);
+
+        // 单个元素：在节点和幽灵位置之间来回移动
+        let mut list = from(&[1]);
+        let mut cursor = list.cursor_back_mut();
+        assert_eq!(cursor.index(), Some(0));
+        cursor.move_next();
+        assert_eq!(cursor.index(), None);
+        cursor.move_next();
+        assert_eq!(cursor.current().map(|v| *v), Some(1));
+        cursor.move_prev();
+        cursor.move_prev();
+        assert_eq!(cursor.current().map(|v| *v), Some(1));
+
+        // 多个元素：经过幽灵位置首尾相连
+        let mut list = from(&[1, 2, 3]);
+        {
+            let mut cursor = list.cursor_front_mut();
+            cursor.move_prev();
+            assert_eq!(cursor.index(), None);
+            cursor.move_prev();
+            assert_eq!(
+                (cursor.index(), cursor.current().map(|v| *v)),
+                (Some(2), Some(3))
+            );
+            cursor.move_prev();
+            *cursor.current().unwrap() = 20;
+            cursor.move_next();
+            cursor.move_next();
+            cursor.move_next();
+            assert_eq!(
+                (cursor.index(), cursor.current().map(|v| *v)),
+                (Some(0), Some(1))
+            );
+        }
+        assert_eq!(contents(&mut list), vec![1, 20, 3]);
+    }
+
+    #[test]
+    fn test_cursor_insert() {
+        // 在幽灵位置插入：insert_after 插入到头部，insert_before 插入到尾部
+        let mut list = List::new();
+        {
+            let mut cursor = list.cursor_front_mut();
+            cursor.insert_after(2);
+            cursor.insert_before(3);
+            cursor.insert_after(1);
+            assert_eq!(cursor.index(), None);
+        }
+        assert_eq!(contents(&mut list), vec![1, 2, 3]);
+
+        {
+            let mut cursor = list.cursor_front_mut();
+            cursor.insert_before(0);
+            assert_eq!(cursor.index(), Some(1));
+            cursor.insert_after(10);
+            assert_eq!(cursor.current().map(|v| *v), Some(1));
+        }
+        assert_eq!(contents(&mut list), vec![0, 1, 10, 2, 3]);
+
+        // 在尾节点之后插入会更新 tail
+        {
+            let mut cursor = list.cursor_back_mut();
+            cursor.insert_after(4);
+            cursor.insert_before(30);
+            assert_eq!(cursor.index(), Some(5));
+        }
+        assert_eq!(contents(&mut list), vec![0, 1, 10, 2, 30, 3, 4]);
+        assert_eq!(list.pop_back(), Some(4));
+        assert_eq!(list.pop_front(), Some(0));
+        assert_eq!(list.len(), 5);
+    }
+
+    #[test]
+    fn test_cursor_remove() {
+        let mut list = from(&[1, 2, 3, 4]);
+        {
+            let mut cursor = list.cursor_front_mut();
+            cursor.move_next();
+            assert_eq!(cursor.remove_current(), Some(2));
+            assert_eq!(
+                (cursor.index(), cursor.current().map(|v| *v)),
+                (Some(1), Some(3))
+            );
+
+            // 删除头节点
+            cursor.move_prev();
+            assert_eq!(cursor.remove_current(), Some(1));
+            assert_eq!(
+                (cursor.index(), cursor.current().map(|v| *v)),
+                (Some(0), Some(3))
+            );
+
+            // 删除尾节点后移动到幽灵位置
+            cursor.move_next();
+            assert_eq!(cursor.remove_current(), Some(4));
+            assert_eq!(cursor.index(), None);
+            assert_eq!(cursor.remove_current(), None);
+        }
+        assert_eq!(contents(&mut list), vec![3]);
+
+        // 删除唯一的节点后链表为空
+        {
+            let mut cursor = list.cursor_back_mut();
+            assert_eq!(cursor.remove_current(), Some(3));
+            assert_eq!(cursor.index(), None);
+        }
+        assert!(list.is_empty());
+        assert!(list.peek_front().is_none() && list.peek_back().is_none());
+        list.push_back(5);
+        assert_eq!(contents(&mut list), vec![5]);
+    }
+
+    #[test]
+    fn test_cursor_split() {
+        let mut list = from(&[1, 2, 3, 4, 5]);
+        let (mut before, mut after) = {
+            let mut cursor = list.cursor_front_mut();
+            cursor.move_next();
+            let after = cursor.split_after();
+            assert_eq!(cursor.index(), Some(1));
+            let before = cursor.split_before();
+            assert_eq!(cursor.index(), Some(0));
+            assert_eq!(cursor.current().map(|v| *v), Some(2));
+            (before, after)
+        };
+        assert_eq!(contents(&mut before), vec![1]);
+        assert_eq!(contents(&mut list), vec![2]);
+        assert_eq!(contents(&mut after), vec![3, 4, 5]);
+
+        // 在两端拆分得到空链表
+        assert!(after.cursor_front_mut().split_before().is_empty());
+        assert!(after.cursor_back_mut().split_after().is_empty());
+        assert_eq!(contents(&mut after), vec![3, 4, 5]);
+
+        // 在幽灵位置拆分得到整个链表
+        let mut all = {
+            let mut cursor = after.cursor_front_mut();
+            cursor.move_prev();
+            cursor.split_after()
+        };
+        assert!(after.is_empty());
+        let mut all = {
+            let mut cursor = all.cursor_back_mut();
+            cursor.move_next();
+            cursor.split_before()
+        };
+        assert_eq!(contents(&mut all), vec![3, 4, 5]);
+
+        let mut empty: List<i32> = List::new();
+        assert!(empty.cursor_front_mut().split_after().is_empty());
+    }
+
+    #[test]
+    fn test_cursor_splice() {
+        let mut list = from(&[1, 5]);
+        {
+            let mut cursor = list.cursor_front_mut();
+            cursor.splice_after(from(&[2, 3]));
+            assert_eq!(cursor.index(), Some(0));
+            cursor.move_next();
+            cursor.move_next();
+            cursor.move_next();
+            cursor.splice_before(from(&[4]));
+            assert_eq!(
+                (cursor.index(), cursor.current().map(|v| *v)),
+                (Some(4), Some(5))
+            );
+        }
+        assert_eq!(contents(&mut list), vec![1, 2, 3, 4, 5]);
+
+        // 拼接到两端和幽灵位置
+        {
+            let mut cursor = list.cursor_back_mut();
+            cursor.splice_after(from(&[6]));
+            cursor.move_next();
+            cursor.move_next();
+            cursor.splice_before(from(&[7, 8]));
+            cursor.splice_after(from(&[-1, 0]));
+            cursor.splice_after(List::new());
+            assert_eq!(cursor.index(), None);
+        }
+        assert_eq!(contents(&mut list), vec![-1, 0, 1, 2, 3, 4, 5, 6, 7, 8]);
+
+        // 拼接到空链表中
+        let mut empty = List::new();
+        empty.cursor_front_mut().splice_before(from(&[1, 2]));
+        assert_eq!(contents(&mut empty), vec![1, 2]);
+        assert_eq!(empty.len(), 2);
+
+        // 在 1 处拆分，再把前后两段交换位置拼接回去
+        {
+            let mut cursor = list.cursor_front_mut();
+            cursor.move_next();
+            cursor.move_next();
+            let after = cursor.split_after();
+            let before = cursor.split_before();
+            cursor.splice_after(before);
+            cursor.splice_before(after);
+            assert_eq!(cursor.index(), Some(7));
+            cursor.move_prev();
+            assert_eq!(cursor.current().map(|v| *v), Some(8));
+        }
+        assert_eq!(contents(&mut list), vec![2, 3, 4, 5, 6, 7, 8, 1, -1, 0]);
+    }
 }
@@ -0,0 +1,356 @@
+/// LRU 缓存：HashMap 保存键值对，fourth_list 双向链表按访问顺序保存键（头部是最久未使用的，尾部是最近使用的）。
+/// HashMap 中同时记录每个键在链表中的节点句柄，这样访问时可以在 O(1) 时间内把节点移动到尾部，
+/// 容量不足时从头部淘汰。与 closure.rs 中的 Cacher 一样，get_or_insert_with 可以用来缓存计算结果
+use std::borrow::Borrow;
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hash};
+
+use super::fourth_list::{Handle, List};
+
+/// get、get_mut 和 get_or_insert_with 的命中统计，peek 不计入
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+impl CacheStats {
+    /// 命中率，还没有访问过时为 0
+    pub fn hit_rate(&self) -> f64 {
+        match self.hits + self.misses {
+            0 => 0.0,
+            total => self.hits as f64 / total as f64,
+        }
+    }
+}
+
+struct Entry<K, V> {
+    value: V,
+    handle: Handle<K>,
+}
+
+pub struct LruCache<K, V, S = RandomState> {
+    map: HashMap<K, Entry<K, V>, S>,
+    order: List<K>,
+    capacity: usize,
+    stats: CacheStats,
+    // 因为容量不足（put、resize）被淘汰时调用，pop_lru 和 remove 直接把值交给调用方，不调用
+    on_evict: Option<Box<dyn FnMut(K, V)>>,
+}
+
+impl<K: Hash + Eq + Clone, V> LruCache<K, V> {
+    /// 容量为 0 时 panic：什么都存不下的缓存没有意义，get_or_insert_with 也无法返回缓存中的值
+    pub fn new(capacity: usize) -> Self {
+        Self::with_hasher(capacity, RandomState::new())
+    }
+}
+
+impl<K: Hash + Eq + Clone, V, S: BuildHasher> LruCache<K, V, S> {
+    /// 容量为 0 时 panic
+    pub fn with_hasher(capacity: usize, hasher: S) -> Self {
+        assert!(capacity > 0, "LruCache capacity must be non-zero");
+        LruCache {
+            map: HashMap::with_hasher(hasher),
+            order: List::new(),
+            capacity,
+            stats: CacheStats::default(),
+            on_evict: None,
+        }
+    }
+
+    /// 设置淘汰回调，每个因为容量不足被淘汰的键值对都会传给 f
+    pub fn on_evict(mut self, f: impl FnMut(K, V) + 'static) -> Self {
+        self.on_evict = Some(Box::new(f));
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.contains_key(key)
+    }
+
+    /// 返回键对应的值并把它标记为最近使用
+    pub fn get<Q>(&mut self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get_mut(key).map(|value| &*value)
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self.map.get_mut(key) {
+            Some(entry) => {
+                self.stats.hits += 1;
+                self.order.move_to_back(&entry.handle);
+                Some(&mut entry.value)
+            }
+            None => {
+                self.stats.misses += 1;
+                None
+            }
+        }
+    }
+
+    /// 只查看值，不改变使用顺序，也不计入统计
+    pub fn peek<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.get(key).map(|entry| &entry.value)
+    }
+
+    /// 插入或者更新键值对并标记为最近使用，返回键原来的值。
+    /// 插入后超出容量时淘汰最久未使用的键值对
+    pub fn put(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(entry) = self.map.get_mut(&key) {
+            self.order.move_to_back(&entry.handle);
+            return Some(std::mem::replace(&mut entry.value, value));
+        }
+
+        let handle = self.order.push_back_handle(key.clone());
+        self.map.insert(key, Entry { value, handle });
+        self.evict_to(self.capacity);
+        None
+    }
+
+    /// 缓存命中时返回缓存的值，否则用 f 计算出值放入缓存再返回
+    pub fn get_or_insert_with(&mut self, key: K, f: impl FnOnce(&K) -> V) -> &V {
+        if self.map.contains_key(&key) {
+            self.stats.hits += 1;
+            let entry = &self.map[&key];
+            self.order.move_to_back(&entry.handle);
+        } else {
+            self.stats.misses += 1;
+            let value = f(&key);
+            // 容量至少为 1，刚放入的键值对是最近使用的，不会被淘汰
+            self.put(key.clone(), value);
+        }
+        &self.map[&key].value
+    }
+
+    /// 删除并返回最久未使用的键值对
+    pub fn pop_lru(&mut self) -> Option<(K, V)> {
+        let key = self.order.pop_front()?;
+        let entry = self.map.remove(&key)?;
+        Some((key, entry.value))
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let entry = self.map.remove(key)?;
+        self.order.remove(&entry.handle);
+        Some(entry.value)
+    }
+
+    /// 修改容量，缩小时按照使用顺序淘汰多出的键值对。容量为 0 时 panic
+    pub fn resize(&mut self, capacity: usize) {
+        assert!(capacity > 0, "LruCache capacity must be non-zero");
+        self.capacity = capacity;
+        self.evict_to(capacity);
+    }
+
+    fn evict_to(&mut self, capacity: usize) {
+        while self.map.len() > capacity {
+            let Some((key, value)) = self.pop_lru() else {
+                break;
+            };
+            if let Some(on_evict) = &mut self.on_evict {
+                on_evict(key, value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CacheStats, LruCache};
+    use crate::linked_list::rng::Rng;
+    use std::cell::RefCell;
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::BuildHasherDefault;
+    use std::rc::Rc;
+
+    type EvictLog = Rc<RefCell<Vec<(&'static str, i32)>>>;
+
+    // 返回缓存和一个记录了所有被淘汰的键值对的 Vec
+    fn cache_with_log(capacity: usize) -> (LruCache<&'static str, i32>, EvictLog) {
+        let evicted = Rc::new(RefCell::new(Vec::new()));
+        let log = evicted.clone();
+        let cache = LruCache::new(capacity).on_evict(move |k, v| log.borrow_mut().push((k, v)));
+        (cache, evicted)
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let (mut cache, evicted) = cache_with_log(2);
+        assert_eq!(cache.put("a", 1), None);
+        assert_eq!(cache.put("b", 2), None);
+        // 访问 a 之后 b 成为最久未使用的
+        assert_eq!(cache.get("a"), Some(&1));
+        cache.put("c", 3);
+        assert_eq!(*evicted.borrow(), vec![("b", 2)]);
+        assert!(!cache.contains("b"));
+
+        // 更新已有的键不会淘汰，但会把它变为最近使用
+        assert_eq!(cache.put("a", 10), Some(1));
+        cache.put("d", 4);
+        assert_eq!(*evicted.borrow(), vec![("b", 2), ("c", 3)]);
+        assert_eq!(cache.len(), 2);
+
+        // peek 不改变使用顺序
+        assert_eq!(cache.peek("a"), Some(&10));
+        cache.put("e", 5);
+        assert_eq!(evicted.borrow().last(), Some(&("a", 10)));
+    }
+
+    #[test]
+    fn pop_lru_and_remove() {
+        let (mut cache, evicted) = cache_with_log(3);
+        cache.put("a", 1);
+        cache.put("b", 2);
+        cache.put("c", 3);
+        *cache.get_mut("a").unwrap() += 10;
+
+        assert_eq!(cache.remove("c"), Some(3));
+        assert_eq!(cache.remove("c"), None);
+        assert_eq!(cache.pop_lru(), Some(("b", 2)));
+        assert_eq!(cache.pop_lru(), Some(("a", 11)));
+        assert_eq!(cache.pop_lru(), None);
+        assert!(cache.is_empty());
+        // 主动删除的不算淘汰
+        assert!(evicted.borrow().is_empty());
+    }
+
+    #[test]
+    fn resize() {
+        let (mut cache, evicted) = cache_with_log(4);
+        for (i, key) in ["a", "b", "c", "d"].into_iter().enumerate() {
+            cache.put(key, i as i32);
+        }
+        cache.get("a");
+        cache.resize(2);
+        assert_eq!(cache.capacity(), 2);
+        assert_eq!(*evicted.borrow(), vec![("b", 1), ("c", 2)]);
+        assert_eq!(cache.pop_lru(), Some(("d", 3)));
+
+        cache.resize(3);
+        cache.put("e", 4);
+        cache.put("f", 5);
+        assert_eq!(cache.len(), 3);
+        assert_eq!(evicted.borrow().len(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "capacity must be non-zero")]
+    fn zero_capacity() {
+        let _: LruCache<i32, i32> = LruCache::new(0);
+    }
+
+    #[test]
+    #[should_panic(expected = "capacity must be non-zero")]
+    fn resize_to_zero() {
+        let (mut cache, _) = cache_with_log(1);
+        cache.put("a", 1);
+        cache.resize(0);
+    }
+
+    #[test]
+    fn stats() {
+        let mut cache = LruCache::new(2);
+        assert_eq!(cache.stats().hit_rate(), 0.0);
+        cache.put(1, "one");
+        cache.get(&1);
+        cache.get(&2);
+        cache.peek(&2);
+        assert_eq!(cache.stats(), CacheStats { hits: 1, misses: 1 });
+
+        // 像 Cacher 一样缓存计算结果，只有第一次会调用闭包
+        let mut calls = 0;
+        for _ in 0..3 {
+            let value = cache.get_or_insert_with(3, |_| {
+                calls += 1;
+                "three"
+            });
+            assert_eq!(*value, "three");
+        }
+        assert_eq!(calls, 1);
+        assert_eq!(cache.stats(), CacheStats { hits: 3, misses: 2 });
+        assert_eq!(cache.stats().hit_rate(), 0.6);
+        // 3 刚刚被访问过，淘汰的是 1
+        cache.put(4, "four");
+        assert!(!cache.contains(&1) && cache.contains(&3));
+    }
+
+    #[test]
+    fn custom_hasher_and_borrowed_keys() {
+        let mut cache: LruCache<String, usize, BuildHasherDefault<DefaultHasher>> =
+            LruCache::with_hasher(2, Default::default());
+        cache.put("rust".to_string(), 4);
+        cache.put("go".to_string(), 2);
+        assert_eq!(cache.get("rust"), Some(&4));
+        cache.put("zig".to_string(), 3);
+        assert_eq!(cache.peek("go"), None);
+        assert_eq!(cache.pop_lru(), Some(("rust".to_string(), 4)));
+    }
+
+    // 与一个按使用顺序排列的 Vec 比较：Vec 的头部是最久未使用的
+    #[test]
+    fn matches_naive_model() {
+        let mut cache = LruCache::new(8);
+        let mut model: Vec<(u64, u64)> = Vec::new();
+        let mut rng = Rng::new();
+        for step in 0..5000 {
+            let seed = rng.next_u64();
+            let key = seed % 16;
+            let position = model.iter().position(|&(k, _)| k == key);
+            if seed.is_multiple_of(3) {
+                let expected = position
+                    .map(|i| model.remove(i))
+                    .inspect(|&entry| model.push(entry));
+                assert_eq!(cache.get(&key).copied(), expected.map(|(_, v)| v));
+            } else {
+                let previous = position.map(|i| model.remove(i).1);
+                model.push((key, step));
+                if model.len() > 8 {
+                    model.remove(0);
+                }
+                assert_eq!(cache.put(key, step), previous);
+            }
+            assert_eq!(cache.len(), model.len());
+        }
+        let mut drained = Vec::new();
+        while let Some(entry) = cache.pop_lru() {
+            drained.push(entry);
+        }
+        assert_eq!(drained, model);
+    }
+}
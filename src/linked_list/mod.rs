@@ -1,4 +1,10 @@
+pub mod arc_list;
+pub mod fifth_list;
 pub mod first_list;
 pub mod fourth_list;
+pub mod lru_cache;
+#[cfg(test)]
+mod rng;
 pub mod second_list;
 pub mod third_list;
+pub mod traits;
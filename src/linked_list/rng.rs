@@ -0,0 +1,27 @@
+/// 测试中共用的伪随机数：固定种子的 xorshift，不依赖随机数库，每次运行得到同样的序列，失败时可以复现
+pub struct Rng(u64);
+
+impl Rng {
+    /// 使用默认的种子
+    pub fn new() -> Self {
+        Rng::with_seed(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// seed 不能为 0，否则序列始终为 0
+    pub fn with_seed(seed: u64) -> Self {
+        assert_ne!(seed, 0, "xorshift seed must be non-zero");
+        Rng(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// 返回 0..bound 中的一个数
+    pub fn below(&mut self, bound: u64) -> u64 {
+        self.next_u64() % bound
+    }
+}
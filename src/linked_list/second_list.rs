@@ -1,3 +1,7 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
 type Link<T> = Option<Box<Node<T>>>;
 
 struct Node<T> {
@@ -40,23 +44,249 @@ impl<T> List<T> {
         self.head.as_mut().map(|node| &mut node.value)
     }
 
-    pub fn into_iter(self) -> IntoIter<T> {
-        IntoIter(self)
+    // 以下的批量操作都只修改节点之间的链接，不移动节点中的值，并且都用循环实现，
+    // 不会因为链表很长而递归过深导致栈溢出
+
+    /// 原地反转链表，O(n)
+    pub fn reverse(&mut self) {
+        let mut reversed = None;
+        let mut cur = self.head.take();
+        while let Some(mut node) = cur {
+            cur = node.next.take();
+            node.next = reversed;
+            reversed = Some(node);
+        }
+        self.head = reversed;
+    }
+
+    /// 把 other 的所有元素按原来的顺序接到链表尾部，other 变为空链表。
+    /// 只有头指针，需要先走到尾部，O(len)
+    pub fn append(&mut self, other: &mut List<T>) {
+        let mut tail = &mut self.head;
+        while let Some(node) = tail {
+            tail = &mut node.next;
+        }
+        *tail = other.head.take();
+    }
+
+    /// 在 at 处拆分：链表保留前 at 个元素，返回剩下的元素。at 大于长度时 panic，与 LinkedList 一致
+    pub fn split_off(&mut self, at: usize) -> List<T> {
+        let mut cur = &mut self.head;
+        for _ in 0..at {
+            match cur {
+                Some(node) => cur = &mut node.next,
+                None => panic!("Cannot split off at a nonexistent index"),
+            }
+        }
+        List { head: cur.take() }
+    }
+
+    /// 只保留 f 返回 true 的元素，保持原来的顺序
+    pub fn retain(&mut self, mut f: impl FnMut(&T) -> bool) {
+        let mut cur = &mut self.head;
+        // 先把节点取出来，保留的节点放回原处，去掉的节点用它的下一个节点代替
+        while let Some(mut node) = cur.take() {
+            if f(&node.value) {
+                cur = &mut cur.insert(node).next;
+            } else {
+                *cur = node.next.take();
+            }
+        }
+    }
+
+    /// 去掉连续重复的元素，只保留每一段中的第一个，与 Vec::dedup 一致
+    pub fn dedup(&mut self)
+    where
+        T: PartialEq,
+    {
+        let mut cur = self.head.as_mut();
+        while let Some(node) = cur {
+            while let Some(mut next) = node.next.take() {
+                if next.value == node.value {
+                    node.next = next.next.take();
+                } else {
+                    node.next = Some(next);
+                    break;
+                }
+            }
+            cur = node.next.as_mut();
+        }
+    }
+
+    /// 稳定的归并排序，见 sort_by
+    pub fn sort(&mut self)
+    where
+        T: Ord,
+    {
+        self.sort_by(T::cmp);
+    }
+
+    pub fn sort_by_key<K: Ord>(&mut self, mut f: impl FnMut(&T) -> K) {
+        self.sort_by(|a, b| f(a).cmp(&f(b)));
+    }
+
+    /// 稳定的自底向上归并排序，O(n log n)，通过重新链接节点完成，不分配内存也不移动值。
+    /// bins[i] 为空或者是一段长度为 2^i 的有序链表：每次取下一个节点，像二进制加一那样
+    /// 与 bins[0]、bins[1]……依次合并进位，最后再把所有的 bin 合并起来。
+    /// 编号越小的 bin 中的元素越靠后，合并时较早的元素在前，相等的元素保持原来的顺序
+    pub fn sort_by(&mut self, mut compare: impl FnMut(&T, &T) -> Ordering) {
+        // usize 个元素最多需要 usize::BITS 个 bin
+        let mut bins: [Link<T>; usize::BITS as usize] = std::array::from_fn(|_| None);
+        let mut rest = self.head.take();
+        while let Some(mut node) = rest {
+            rest = node.next.take();
+            let mut carry = Some(node);
+            let mut i = 0;
+            while let Some(bin) = bins[i].take() {
+                carry = merge(Some(bin), carry, &mut compare);
+                i += 1;
+            }
+            bins[i] = carry;
+        }
+
+        let mut sorted = None;
+        for bin in bins {
+            sorted = merge(bin, sorted, &mut compare);
+        }
+        self.head = sorted;
     }
 
-    pub fn iter(&self) -> Iter<T> {
+    pub fn iter(&self) -> Iter<'_, T> {
         Iter {
             next: self.head.as_deref(),
         }
     }
 
-    pub fn iter_mut(&mut self) -> IterMut<T> {
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
         IterMut {
             next: self.head.as_deref_mut(),
         }
     }
 }
 
+// 合并两段有序链表，a 中的元素在原链表中位于 b 之前，相等时先取 a 中的元素以保证稳定
+fn merge<T>(
+    mut a: Link<T>,
+    mut b: Link<T>,
+    compare: &mut impl FnMut(&T, &T) -> Ordering,
+) -> Link<T> {
+    let mut head = None;
+    let mut tail = &mut head;
+    loop {
+        let source = match (&a, &b) {
+            (Some(x), Some(y)) if compare(&y.value, &x.value) == Ordering::Less => &mut b,
+            (Some(_), Some(_)) => &mut a,
+            _ => break,
+        };
+        let Some(mut node) = source.take() else {
+            unreachable!()
+        };
+        *source = node.next.take();
+        tail = &mut tail.insert(node).next;
+    }
+    *tail = a.or(b);
+    head
+}
+
+impl<T> Default for List<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// 以下标准库 trait 的语义与 std::collections::LinkedList 一致：元素的顺序就是 iter 的顺序（从头到尾），
+// 所以 collect 和 extend 把元素依次追加到尾部，而不是像 push 那样放到头部
+
+impl<T> Extend<T> for List<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        // 先找到最后一个节点的 next，之后每追加一个节点就移动到新节点的 next，整体是 O(len + n)
+        let mut tail = &mut self.head;
+        while let Some(node) = tail {
+            tail = &mut node.next;
+        }
+        for value in iter {
+            tail = &mut tail.insert(Box::new(Node { value, next: None })).next;
+        }
+    }
+}
+
+impl<T> FromIterator<T> for List<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = List::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T: Clone> Clone for List<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for List<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for List<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.iter().eq(other)
+    }
+}
+
+impl<T: Eq> Eq for List<T> {}
+
+impl<T: PartialOrd> PartialOrd for List<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other)
+    }
+}
+
+impl<T: Ord> Ord for List<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other)
+    }
+}
+
+impl<T: Hash> Hash for List<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // 和 LinkedList 一样先写入长度，这样嵌套的链表 [[1], [2]] 和 [[1, 2]] 的哈希值不同
+        state.write_usize(self.iter().count());
+        for value in self {
+            value.hash(state);
+        }
+    }
+}
+
+impl<T> IntoIterator for List<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter(self)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a List<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut List<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> IterMut<'a, T> {
+        self.iter_mut()
+    }
+}
+
 pub struct IntoIter<T>(List<T>);
 
 impl<T> Iterator for IntoIter<T> {
@@ -111,6 +341,8 @@ impl<T> Drop for List<T> {
 #[cfg(test)]
 mod test {
     use super::List;
+    use crate::linked_list::rng::Rng;
+    use std::cmp::Ordering;
 
     #[test]
     fn test_list() {
@@ -202,4 +434,162 @@ mod test {
         assert_eq!(iter.next(), Some(&mut 1));
         assert_eq!(list.pop(), Some(33));
     }
+
+    #[test]
+    fn test_std_traits() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::collections::LinkedList;
+        use std::hash::{Hash, Hasher};
+
+        fn hash<T: Hash>(value: &T) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            value.hash(&mut hasher);
+            hasher.finish()
+        }
+
+        // collect 和 extend 保持迭代的顺序，与 LinkedList 相同
+        let mut list: List<i32> = (1..=3).collect();
+        list.extend(vec![4, 5]);
+        let std_list: LinkedList<i32> = (1..=5).collect();
+        assert!(list.iter().eq(&std_list));
+        assert_eq!(format!("{:?}", list), format!("{:?}", std_list));
+        assert_eq!(hash(&list), hash(&std_list));
+
+        let mut empty = List::default();
+        assert_eq!(format!("{:?}", empty), "[]");
+        empty.extend(Some(1));
+        assert_eq!(empty.pop(), Some(1));
+
+        // 克隆得到独立的链表
+        let mut copy = list.clone();
+        assert_eq!(copy, list);
+        for value in &mut copy {
+            *value *= 10;
+        }
+        assert_ne!(copy, list);
+        assert_eq!((&list).into_iter().sum::<i32>(), 15);
+        assert_eq!(
+            copy.into_iter().collect::<Vec<_>>(),
+            vec![10, 20, 30, 40, 50]
+        );
+
+        // 按字典序比较，较短的前缀更小
+        let prefix: List<i32> = (1..=2).collect();
+        let bigger: List<i32> = vec![1, 3].into_iter().collect();
+        assert!(prefix < list && list < bigger);
+        assert_eq!(list.cmp(&(1..=5).collect()), Ordering::Equal);
+        let nan: List<f64> = vec![f64::NAN].into_iter().collect();
+        assert_eq!(nan.partial_cmp(&nan), None);
+
+        let nested: List<List<i32>> = vec![(1..=1).collect(), (2..=2).collect()]
+            .into_iter()
+            .collect();
+        let flat: List<List<i32>> = vec![(1..=2).collect()].into_iter().collect();
+        assert_ne!(hash(&nested), hash(&flat));
+    }
+
+    // 固定种子的伪随机序列，每个值都在 0..bound 中
+    fn random(len: usize, bound: u64) -> Vec<u64> {
+        let mut rng = Rng::new();
+        (0..len).map(|_| rng.below(bound)).collect()
+    }
+
+    #[test]
+    fn test_reverse_append_split_off() {
+        let mut list: List<i32> = (1..=5).collect();
+        list.reverse();
+        assert_eq!(
+            list.iter().copied().collect::<Vec<_>>(),
+            vec![5, 4, 3, 2, 1]
+        );
+
+        let mut other: List<i32> = (6..=7).collect();
+        list.append(&mut other);
+        assert!(other.peek().is_none());
+        assert_eq!(list, vec![5, 4, 3, 2, 1, 6, 7].into_iter().collect());
+        other.append(&mut list);
+        assert_eq!(other.iter().count(), 7);
+
+        let tail = other.split_off(5);
+        assert_eq!(tail, (6..=7).collect());
+        assert_eq!(other, vec![5, 4, 3, 2, 1].into_iter().collect());
+        assert!(other.split_off(5).peek().is_none());
+        let all = other.split_off(0);
+        assert!(other.peek().is_none());
+        assert_eq!(all.iter().count(), 5);
+
+        let mut empty: List<i32> = List::new();
+        empty.reverse();
+        assert!(empty.split_off(0).peek().is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "nonexistent index")]
+    fn test_split_off_out_of_bounds() {
+        let mut list: List<i32> = (1..=3).collect();
+        list.split_off(4);
+    }
+
+    #[test]
+    fn test_retain_dedup() {
+        let mut list: List<i32> = (1..=10).collect();
+        list.retain(|v| v % 3 != 1);
+        assert_eq!(list, vec![2, 3, 5, 6, 8, 9].into_iter().collect());
+        list.retain(|_| false);
+        assert!(list.peek().is_none());
+
+        let mut list: List<i32> = vec![1, 1, 2, 2, 2, 1, 3, 3].into_iter().collect();
+        list.dedup();
+        assert_eq!(list, vec![1, 2, 1, 3].into_iter().collect());
+    }
+
+    #[test]
+    fn test_sort() {
+        let mut list: List<i32> = vec![3, 1, 2, 5, 4, 1].into_iter().collect();
+        list.sort();
+        assert_eq!(list, vec![1, 1, 2, 3, 4, 5].into_iter().collect());
+        list.sort_by(|a, b| b.cmp(a));
+        assert_eq!(list, vec![5, 4, 3, 2, 1, 1].into_iter().collect());
+
+        // 稳定：键相同的元素保持原来的相对顺序
+        let values = random(1000, 10);
+        let mut list: List<(u64, usize)> = values.iter().copied().zip(0..).collect();
+        let mut expected: Vec<_> = list.iter().copied().collect();
+        list.sort_by_key(|&(key, _)| key);
+        expected.sort_by_key(|&(key, _)| key);
+        assert!(list.iter().eq(&expected));
+
+        let mut empty: List<i32> = List::new();
+        empty.sort();
+        assert!(empty.peek().is_none());
+    }
+
+    #[test]
+    fn test_long_list_operations() {
+        let values = random(100_000, 1000);
+        let mut list: List<u64> = values.iter().copied().collect();
+
+        list.reverse();
+        assert!(list.iter().eq(values.iter().rev()));
+        list.reverse();
+
+        let mut tail = list.split_off(50_000);
+        assert!(tail.iter().eq(&values[50_000..]));
+        list.append(&mut tail);
+        assert!(list.iter().eq(&values));
+
+        let mut sorted = values.clone();
+        sorted.sort();
+        let mut sorted_list = list.clone();
+        sorted_list.sort();
+        assert!(sorted_list.iter().eq(&sorted));
+
+        sorted.dedup();
+        sorted_list.dedup();
+        assert!(sorted_list.iter().eq(&sorted));
+
+        list.retain(|v| v % 2 == 0);
+        assert!(list.iter().eq(values.iter().filter(|v| *v % 2 == 0)));
+        drop(list);
+    }
 }
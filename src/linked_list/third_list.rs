@@ -14,6 +14,12 @@ pub struct List<T> {
     head: Link<T>,
 }
 
+impl<T> Default for List<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<T> List<T> {
     pub fn new() -> Self {
         List { head: None }
@@ -40,44 +46,154 @@ impl<T> List<T> {
         self.head.as_ref().map(|node| &node.value)
     }
 
-    // pub fn into_iter(self) -> IntoIter<T> {
-    //     IntoIter(self)
-    // }
-
-    pub fn iter(&self) -> Iter<T> {
+    pub fn iter(&self) -> Iter<'_, T> {
         Iter {
             next: self.head.as_deref(),
         }
     }
 
-    pub fn iter_mut(&mut self) -> IterMut<T> {
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
         let mut next = None;
         if let Some(ref mut node) = self.head {
             next = Rc::get_mut(node);
         }
         IterMut { next }
     }
+
+    // 以下的组合操作都不修改原来的链表，而是返回新的链表，并尽量与原来的链表共享节点：
+    // 单向链表只能共享尾部，所以变化的部分之前的节点需要复制一份（T: Clone），之后的节点直接共享
+
+    /// 跳过前 n 个元素（与 Iterator::skip 相同），剩下的部分完全共享，不复制任何节点。
+    /// 这就是需求中与 take 成对的 drop 操作，命名为 skip 是为了不与 Drop::drop 以及 std::mem::drop 混淆
+    pub fn skip(&self, n: usize) -> List<T> {
+        List {
+            head: self.nodes().nth(n).cloned(),
+        }
+    }
+
+    /// 从头到尾依次用 f 合并每个元素
+    pub fn fold<B>(&self, init: B, f: impl FnMut(B, &T) -> B) -> B {
+        self.iter().fold(init, f)
+    }
+
+    /// 元素类型变了，无法共享任何节点
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> List<U> {
+        List::from_parts(self.iter().map(f).collect(), None)
+    }
+
+    // 依次返回每个节点的 Rc，用来共享某个节点开始的尾部
+    fn nodes(&self) -> impl Iterator<Item = &Rc<Node<T>>> {
+        std::iter::successors(self.head.as_ref(), |node| node.next.as_ref())
+    }
+
+    // 把 values 按顺序放在 tail 前面，得到 values 后接 tail 的链表
+    fn from_parts(values: Vec<T>, tail: Link<T>) -> List<T> {
+        let head = values
+            .into_iter()
+            .rev()
+            .fold(tail, |next, value| Some(Rc::new(Node { value, next })));
+        List { head }
+    }
 }
 
-// // Rc（引用计数）类型是用于共享所有权的智能指针，因此不直接支持所有权转移
-// pub struct IntoIter<T>(List<T>);
-
-// impl<T> Iterator for IntoIter<T> {
-//     type Item = T;
-//     fn next(&mut self) -> Option<Self::Item> {
-//         self.0.head.take().and_then(|rc_node| {
-//             if Rc::strong_count(&rc_node) > 0 {
-//                 // let mut node = Rc::unwrap_or_clone(rc_node);
-//                 // self.0.head = node.next.take();
-//                 // Some(node.value)
-//                 self.0.head = rc_node.next.take();
-//                 Some(rc_node.value)
-//             } else {
-//                 None
-//             }
-//         })
-//     }
-// }
+impl<T: Clone> List<T> {
+    /// 取出头部的元素，链表变为原来的 tail：链表独占头节点时把值移动出来，否则克隆一份
+    pub fn pop(&mut self) -> Option<T> {
+        self.head
+            .take()
+            .map(|rc_node| match Rc::try_unwrap(rc_node) {
+                Ok(node) => {
+                    self.head = node.next;
+                    node.value
+                }
+                // 共享的节点之后的节点也都是共享的，只增加下一个节点的引用计数，不会修改其他链表
+                Err(rc_node) => {
+                    self.head = rc_node.next.clone();
+                    rc_node.value.clone()
+                }
+            })
+    }
+
+    /// 复制自身的节点，后面完全共享 other
+    pub fn append(&self, other: &List<T>) -> List<T> {
+        List::from_parts(self.iter().cloned().collect(), other.head.clone())
+    }
+
+    /// 每个节点的 next 都变了，需要复制所有的节点
+    pub fn reverse(&self) -> List<T> {
+        List {
+            head: self.fold(None, |next, value| {
+                Some(Rc::new(Node {
+                    value: value.clone(),
+                    next,
+                }))
+            }),
+        }
+    }
+
+    /// 只保留 f 返回 true 的元素：最后一个被去掉的元素之后的部分原样共享，之前保留的元素需要复制
+    pub fn filter(&self, mut f: impl FnMut(&T) -> bool) -> List<T> {
+        let mut copied = Vec::new();
+        // 上一个被去掉的元素之后连续保留的节点
+        let mut kept = Vec::new();
+        for node in self.nodes() {
+            if f(&node.value) {
+                kept.push(node);
+            } else {
+                copied.extend(kept.drain(..).map(|node| node.value.clone()));
+            }
+        }
+        List::from_parts(copied, kept.first().map(|&node| node.clone()))
+    }
+
+    /// 前 n 个元素，n 不小于长度时直接共享整个链表
+    pub fn take(&self, n: usize) -> List<T> {
+        if self.nodes().nth(n).is_none() {
+            return List {
+                head: self.head.clone(),
+            };
+        }
+        List::from_parts(self.iter().take(n).cloned().collect(), None)
+    }
+
+    /// 把两个链表的元素按位置组成二元组，长度取较短的一个
+    pub fn zip<U: Clone>(&self, other: &List<U>) -> List<(T, U)> {
+        List::from_parts(
+            self.iter().cloned().zip(other.iter().cloned()).collect(),
+            None,
+        )
+    }
+
+    /// 把第 index 个元素替换为 value，复制它之前的节点，共享它之后的节点。
+    /// index 超出范围时返回 None
+    pub fn update_at(&self, index: usize, value: T) -> Option<List<T>> {
+        let node = self.nodes().nth(index)?;
+        let mut values: Vec<T> = self.iter().take(index).cloned().collect();
+        values.push(value);
+        Some(List::from_parts(values, node.next.clone()))
+    }
+}
+
+// Rc（引用计数）类型是用于共享所有权的智能指针，节点可能同时属于多个链表，因此不能总是转移所有权：
+// 链表独占节点时把值移动出来，节点被其他链表共享时只能克隆一份，所以要求 T: Clone
+impl<T: Clone> IntoIterator for List<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter(self)
+    }
+}
+
+pub struct IntoIter<T>(List<T>);
+
+impl<T: Clone> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.pop()
+    }
+}
 
 pub struct Iter<'a, T> {
     next: Option<&'a Node<T>>,
@@ -127,6 +243,7 @@ impl<T> Drop for List<T> {
 #[cfg(test)]
 mod tests {
     use super::List;
+    use crate::linked_list::rng::Rng;
 
     #[test]
     pub fn test_basic() {
@@ -149,29 +266,155 @@ mod tests {
         assert_eq!(list.peek(), None);
     }
 
-    // // todo: into_iter的写法有问题
-    // #[test]
-    // fn test_into_iter() {
-    //     let mut list = List::new();
-    //     assert_eq!(list.peek(), None);
-    //     list = list.prepend(4).prepend(5).prepend(6);
-    //     let list2 = list.prepend(7).prepend(8).prepend(9);
-
-    //     let mut iter = list.into_iter();
-    //     assert_eq!(iter.next(), Some(6));
-    //     assert_eq!(iter.next(), Some(5));
-    //     assert_eq!(iter.next(), Some(4));
-    //     assert_eq!(iter.next(), None);
-
-    //     let mut iter = list2.into_iter();
-    //     assert_eq!(iter.next(), Some(9));
-    //     assert_eq!(iter.next(), Some(8));
-    //     assert_eq!(iter.next(), Some(7));
-    //     assert_eq!(iter.next(), Some(6));
-    //     assert_eq!(iter.next(), Some(5));
-    //     assert_eq!(iter.next(), Some(4));
-    //     assert_eq!(iter.next(), None);
-    // }
+    #[test]
+    fn test_into_iter() {
+        let mut list = List::new();
+        assert_eq!(list.peek(), None);
+        list = list.prepend(4).prepend(5).prepend(6);
+        let list2 = list.prepend(7).prepend(8).prepend(9);
+
+        let mut iter = list.into_iter();
+        assert_eq!(iter.next(), Some(6));
+        assert_eq!(iter.next(), Some(5));
+        assert_eq!(iter.next(), Some(4));
+        assert_eq!(iter.next(), None);
+
+        let mut iter = list2.into_iter();
+        assert_eq!(iter.next(), Some(9));
+        assert_eq!(iter.next(), Some(8));
+        assert_eq!(iter.next(), Some(7));
+        assert_eq!(iter.next(), Some(6));
+        assert_eq!(iter.next(), Some(5));
+        assert_eq!(iter.next(), Some(4));
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn test_into_iter_moves_unique_nodes() {
+        use std::rc::Rc;
+
+        // 用 Rc 作为元素，通过引用计数区分值是被移动出来的还是克隆出来的
+        let shared = List::new().prepend(Rc::new(1));
+        let list = shared.prepend(Rc::new(2));
+
+        let mut iter = list.into_iter();
+        let two = iter.next().unwrap();
+        assert_eq!(Rc::strong_count(&two), 1);
+        let one = iter.next().unwrap();
+        assert_eq!(Rc::strong_count(&one), 2);
+        assert!(iter.next().is_none());
+
+        // 迭代不会修改共享的部分
+        assert!(Rc::ptr_eq(shared.peek().unwrap(), &one));
+        drop(iter);
+        let mut iter = shared.into_iter();
+        assert_eq!(iter.next(), Some(one));
+    }
+
+    fn from(values: &[i32]) -> List<i32> {
+        values
+            .iter()
+            .rev()
+            .fold(List::new(), |list, &v| list.prepend(v))
+    }
+
+    fn to_vec<T: Clone>(list: &List<T>) -> Vec<T> {
+        list.iter().cloned().collect()
+    }
+
+    // 两个链表从头开始是否是同一串节点（而不只是值相等）
+    fn shares(a: &List<i32>, b: &List<i32>) -> bool {
+        match (&a.head, &b.head) {
+            (Some(a), Some(b)) => std::rc::Rc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+
+    #[test]
+    fn test_combinators_share_structure() {
+        let list = from(&[1, 2, 3, 4, 5]);
+
+        let skipped = list.skip(2);
+        assert_eq!(to_vec(&skipped), vec![3, 4, 5]);
+        assert!(shares(&skipped, &list.tail().tail()));
+        assert!(list.skip(9).peek().is_none());
+
+        let other = from(&[6, 7]);
+        let appended = list.append(&other);
+        assert_eq!(to_vec(&appended), vec![1, 2, 3, 4, 5, 6, 7]);
+        assert!(shares(&appended.skip(5), &other));
+
+        // 去掉 2 以后 3、4、5 原样共享
+        let filtered = list.filter(|&v| v != 2);
+        assert_eq!(to_vec(&filtered), vec![1, 3, 4, 5]);
+        assert!(shares(&filtered.tail(), &list.skip(2)));
+        assert!(shares(&list.filter(|&v| v > 0), &list));
+
+        let updated = list.update_at(1, 20).unwrap();
+        assert_eq!(to_vec(&updated), vec![1, 20, 3, 4, 5]);
+        assert!(shares(&updated.skip(2), &list.skip(2)));
+        assert!(list.update_at(5, 0).is_none());
+
+        assert!(shares(&list.take(5), &list));
+        assert_eq!(to_vec(&list), vec![1, 2, 3, 4, 5]);
+    }
+
+    // 用固定种子的伪随机数生成一批链表，比较每个组合操作与 Vec 上等价操作的结果
+    #[test]
+    fn test_combinators_match_vec() {
+        let mut rng = Rng::with_seed(0x9e37_79b9_7f4a_7c15);
+        let mut next = move |bound: usize| rng.below(bound as u64) as usize;
+
+        for _ in 0..500 {
+            let a: Vec<i32> = (0..next(12)).map(|_| next(10) as i32).collect();
+            let b: Vec<i32> = (0..next(12)).map(|_| next(10) as i32).collect();
+            let (la, lb) = (from(&a), from(&b));
+            let n = next(15);
+            let threshold = next(10) as i32;
+
+            assert_eq!(to_vec(&la), a);
+            assert_eq!(to_vec(&la.append(&lb)), [a.clone(), b.clone()].concat());
+            assert_eq!(
+                to_vec(&la.reverse()),
+                a.iter().rev().copied().collect::<Vec<_>>()
+            );
+            assert_eq!(
+                to_vec(&la.map(|v| v * 2 + 1)),
+                a.iter().map(|v| v * 2 + 1).collect::<Vec<_>>()
+            );
+            assert_eq!(
+                to_vec(&la.filter(|&v| v < threshold)),
+                a.iter()
+                    .copied()
+                    .filter(|&v| v < threshold)
+                    .collect::<Vec<_>>()
+            );
+            assert_eq!(to_vec(&la.take(n)), a[..n.min(a.len())].to_vec());
+            assert_eq!(to_vec(&la.skip(n)), a[n.min(a.len())..].to_vec());
+            assert_eq!(
+                to_vec(&la.zip(&lb)),
+                a.iter().copied().zip(b.iter().copied()).collect::<Vec<_>>()
+            );
+            assert_eq!(
+                la.fold(Vec::new(), |mut acc, &v| {
+                    acc.push(v);
+                    acc
+                }),
+                a
+            );
+            assert_eq!(
+                la.fold(0, |acc, v| acc * 3 + v),
+                a.iter().fold(0, |acc, v| acc * 3 + v)
+            );
+
+            let mut updated = a.clone();
+            let expected = updated.get_mut(n).map(|v| *v = -1).map(|_| updated);
+            assert_eq!(la.update_at(n, -1).map(|list| to_vec(&list)), expected);
+            // 原来的链表始终不变
+            assert_eq!(to_vec(&la), a);
+        }
+    }
 
     #[test]
     fn test_iter() {
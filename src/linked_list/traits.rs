@@ -0,0 +1,344 @@
+/// 所有链表共同的操作：单向链表只能在头部进出，实现 Stack；双向链表两端都能进出，实现 Deque。
+/// 各个链表保留自己原来的方法名，这里的实现只是把原来的方法对应到统一的名字上，
+/// 这样就可以用同一套一致性测试（tests 中的 check_stack / check_deque）检查每一种链表
+use std::cell::Ref;
+use std::ops::Deref;
+
+use super::{arc_list, fifth_list, first_list, fourth_list, second_list, third_list};
+
+pub trait Stack<T> {
+    /// peek 返回的引用：大多数链表是 &T，fourth_list 的元素放在 RefCell 中，只能返回 Ref<T>
+    type Peek<'a>: Deref<Target = T>
+    where
+        Self: 'a;
+
+    fn push(&mut self, value: T);
+
+    fn pop(&mut self) -> Option<T>;
+
+    fn peek(&self) -> Option<Self::Peek<'_>>;
+
+    fn is_empty(&self) -> bool {
+        self.peek().is_none()
+    }
+}
+
+/// 双端队列，作为 Stack 使用时在头部进出
+pub trait Deque<T>: Stack<T> {
+    fn push_front(&mut self, value: T);
+
+    fn push_back(&mut self, value: T);
+
+    fn pop_front(&mut self) -> Option<T>;
+
+    fn pop_back(&mut self) -> Option<T>;
+
+    fn peek_front(&self) -> Option<Self::Peek<'_>>;
+
+    fn peek_back(&self) -> Option<Self::Peek<'_>>;
+}
+
+// first_list 只能存放 i32
+impl Stack<i32> for first_list::List {
+    type Peek<'a> = &'a i32;
+
+    fn push(&mut self, value: i32) {
+        first_list::List::push(self, value)
+    }
+
+    fn pop(&mut self) -> Option<i32> {
+        first_list::List::pop(self)
+    }
+
+    fn peek(&self) -> Option<&i32> {
+        first_list::List::peek(self)
+    }
+}
+
+impl<T> Stack<T> for second_list::List<T> {
+    type Peek<'a>
+        = &'a T
+    where
+        T: 'a;
+
+    fn push(&mut self, value: T) {
+        second_list::List::push(self, value)
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        second_list::List::pop(self)
+    }
+
+    fn peek(&self) -> Option<&T> {
+        second_list::List::peek(self)
+    }
+}
+
+// 持久化链表：push 和 pop 把链表替换为 prepend 和 tail 的结果，共享的节点只能克隆出其中的值
+impl<T: Clone> Stack<T> for third_list::List<T> {
+    type Peek<'a>
+        = &'a T
+    where
+        T: 'a;
+
+    fn push(&mut self, value: T) {
+        *self = self.prepend(value);
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        third_list::List::pop(self)
+    }
+
+    fn peek(&self) -> Option<&T> {
+        third_list::List::peek(self)
+    }
+}
+
+impl<T: Clone> Stack<T> for arc_list::List<T> {
+    type Peek<'a>
+        = &'a T
+    where
+        T: 'a;
+
+    fn push(&mut self, value: T) {
+        *self = self.prepend(value);
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        arc_list::List::pop(self)
+    }
+
+    fn peek(&self) -> Option<&T> {
+        arc_list::List::peek(self)
+    }
+}
+
+impl<T> Stack<T> for fourth_list::List<T> {
+    type Peek<'a>
+        = Ref<'a, T>
+    where
+        T: 'a;
+
+    fn push(&mut self, value: T) {
+        self.push_front(value)
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        fourth_list::List::pop_front(self)
+    }
+
+    fn peek(&self) -> Option<Ref<'_, T>> {
+        fourth_list::List::peek_front(self)
+    }
+}
+
+impl<T> Deque<T> for fourth_list::List<T> {
+    fn push_front(&mut self, value: T) {
+        fourth_list::List::push_front(self, value)
+    }
+
+    fn push_back(&mut self, value: T) {
+        fourth_list::List::push_back(self, value)
+    }
+
+    fn pop_front(&mut self) -> Option<T> {
+        fourth_list::List::pop_front(self)
+    }
+
+    fn pop_back(&mut self) -> Option<T> {
+        fourth_list::List::pop_back(self)
+    }
+
+    fn peek_front(&self) -> Option<Ref<'_, T>> {
+        fourth_list::List::peek_front(self)
+    }
+
+    fn peek_back(&self) -> Option<Ref<'_, T>> {
+        fourth_list::List::peek_back(self)
+    }
+}
+
+impl<T> Stack<T> for fifth_list::List<T> {
+    type Peek<'a>
+        = &'a T
+    where
+        T: 'a;
+
+    fn push(&mut self, value: T) {
+        self.push_front(value)
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        fifth_list::List::pop_front(self)
+    }
+
+    fn peek(&self) -> Option<&T> {
+        self.front()
+    }
+
+    fn is_empty(&self) -> bool {
+        fifth_list::List::is_empty(self)
+    }
+}
+
+impl<T> Deque<T> for fifth_list::List<T> {
+    fn push_front(&mut self, value: T) {
+        fifth_list::List::push_front(self, value)
+    }
+
+    fn push_back(&mut self, value: T) {
+        fifth_list::List::push_back(self, value)
+    }
+
+    fn pop_front(&mut self) -> Option<T> {
+        fifth_list::List::pop_front(self)
+    }
+
+    fn pop_back(&mut self) -> Option<T> {
+        fifth_list::List::pop_back(self)
+    }
+
+    fn peek_front(&self) -> Option<&T> {
+        self.front()
+    }
+
+    fn peek_back(&self) -> Option<&T> {
+        self.back()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::linked_list::rng::Rng;
+    use std::collections::VecDeque;
+
+    // 新增的链表只要实现 Stack 或 Deque，再在下面加一个调用 check_stack / check_deque 的测试即可
+
+    #[test]
+    fn first_list() {
+        check_stack(first_list::List::new);
+    }
+
+    #[test]
+    fn second_list() {
+        check_stack(second_list::List::new);
+    }
+
+    #[test]
+    fn third_list() {
+        check_stack(third_list::List::new);
+    }
+
+    #[test]
+    fn arc_list() {
+        check_stack(arc_list::List::new);
+    }
+
+    #[test]
+    fn fourth_list() {
+        check_deque(fourth_list::List::new);
+    }
+
+    #[test]
+    fn fifth_list() {
+        check_deque(fifth_list::List::new);
+    }
+
+    fn peek<S: Stack<i32>>(stack: &S) -> Option<i32> {
+        stack.peek().map(|value| *value)
+    }
+
+    fn check_stack<S: Stack<i32>>(new: impl Fn() -> S) {
+        // 后进先出
+        let mut stack = new();
+        assert!(stack.is_empty());
+        assert_eq!(stack.pop(), None);
+        assert_eq!(peek(&stack), None);
+        stack.push(1);
+        stack.push(2);
+        stack.push(3);
+        assert_eq!(peek(&stack), Some(3));
+        assert_eq!(stack.pop(), Some(3));
+        stack.push(4);
+        assert_eq!(stack.pop(), Some(4));
+        assert_eq!(stack.pop(), Some(2));
+        assert_eq!(stack.pop(), Some(1));
+        assert_eq!(stack.pop(), None);
+        assert!(stack.is_empty());
+
+        // 与 Vec 在同一串随机操作下比较
+        let mut rng = Rng::new();
+        let mut stack = new();
+        let mut oracle = Vec::new();
+        for _ in 0..5000 {
+            if rng.below(3) < 2 {
+                let value = rng.below(1000) as i32;
+                stack.push(value);
+                oracle.push(value);
+            } else {
+                assert_eq!(stack.pop(), oracle.pop());
+            }
+            assert_eq!(peek(&stack), oracle.last().copied());
+            assert_eq!(stack.is_empty(), oracle.is_empty());
+        }
+        while let Some(value) = oracle.pop() {
+            assert_eq!(stack.pop(), Some(value));
+        }
+        assert_eq!(stack.pop(), None);
+
+        // 很长的链表在 drop 时不能因为递归而栈溢出
+        let mut stack = new();
+        for i in 0..100_000 {
+            stack.push(i);
+        }
+        drop(stack);
+    }
+
+    fn check_deque<D: Deque<i32>>(new: impl Fn() -> D) {
+        check_stack(&new);
+
+        let mut deque = new();
+        assert_eq!(deque.pop_back(), None);
+        assert!(deque.peek_back().is_none());
+        deque.push_back(2);
+        deque.push_front(1);
+        deque.push_back(3);
+        assert_eq!(deque.peek_front().map(|v| *v), Some(1));
+        assert_eq!(deque.peek_back().map(|v| *v), Some(3));
+        // 作为 Stack 使用时在头部进出
+        deque.push(0);
+        assert_eq!(deque.pop_back(), Some(3));
+        assert_eq!(deque.pop(), Some(0));
+        assert_eq!(deque.pop_front(), Some(1));
+        assert_eq!(deque.pop_back(), Some(2));
+        assert!(deque.is_empty());
+
+        // 与 VecDeque 在同一串随机操作下比较
+        let mut rng = Rng::with_seed(0x9e37_79b9_7f4a_7c15);
+        let mut deque = new();
+        let mut oracle = VecDeque::new();
+        for _ in 0..5000 {
+            let value = rng.below(1000) as i32;
+            match rng.below(6) {
+                0 | 1 => {
+                    deque.push_front(value);
+                    oracle.push_front(value);
+                }
+                2 | 3 => {
+                    deque.push_back(value);
+                    oracle.push_back(value);
+                }
+                4 => assert_eq!(deque.pop_front(), oracle.pop_front()),
+                _ => assert_eq!(deque.pop_back(), oracle.pop_back()),
+            }
+            assert_eq!(deque.peek_front().map(|v| *v), oracle.front().copied());
+            assert_eq!(deque.peek_back().map(|v| *v), oracle.back().copied());
+            assert_eq!(deque.is_empty(), oracle.is_empty());
+        }
+        while let Some(value) = oracle.pop_back() {
+            assert_eq!(deque.pop_back(), Some(value));
+        }
+        assert_eq!(deque.pop_front(), None);
+    }
+}
@@ -0,0 +1,245 @@
+/// minigrep 的异步版本：基于 tokio 读取文件，以 Stream 的形式逐个产出匹配的行，不会阻塞运行时
+use std::io;
+use std::path::{Path, PathBuf};
+
+use futures::stream::{self, Stream, TryStreamExt};
+use tokio::fs::File;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, BufReader, Lines};
+
+use super::cancel::Cancel;
+use super::stats::Stats;
+use super::{collect_files, matcher};
+
+// unfold 的状态：出错或读完以后置为 None，Stream 随之结束
+type State<R> = Option<(Lines<R>, String)>;
+
+/// 逐行读取 reader，返回由包含 key 的行组成的 Stream。
+/// Stream 拥有 reader 的所有权，直接 drop 掉 Stream 就能取消搜索，剩下的内容不会再被读取。
+pub fn search_lines<R>(
+    key: &str,
+    reader: R,
+    ignore_case: bool,
+) -> impl Stream<Item = io::Result<String>>
+where
+    R: AsyncBufRead + Unpin,
+{
+    let state: State<R> = Some((reader.lines(), key.to_string()));
+
+    stream::unfold(state, move |state| async move {
+        let (mut lines, key) = state?;
+        loop {
+            match lines.next_line().await {
+                Ok(Some(line)) => {
+                    // 与同步版本使用同一套匹配逻辑，忽略大小写时逐个字符比较小写形式
+                    if matcher::is_match(&key, &line, ignore_case) {
+                        return Some((Ok(line), Some((lines, key))));
+                    }
+                }
+                Ok(None) => return None,
+                // 读取出错后不再继续，把错误交给调用者
+                Err(err) => return Some((Err(err), None)),
+            }
+        }
+    })
+}
+
+/// 使用 tokio::fs 打开文件，返回匹配行组成的 Stream
+pub async fn search_file<P: AsRef<Path>>(
+    key: &str,
+    path: P,
+    ignore_case: bool,
+) -> io::Result<impl Stream<Item = io::Result<String>>> {
+    let file = File::open(path).await?;
+    Ok(search_lines(key, BufReader::new(file), ignore_case))
+}
+
+/// 给运行在 tokio 上的服务调用的库接口：目录按同步版本的规则（collect_files）展开成文件，
+/// 依次搜索每个文件，返回每个文件的路径和其中匹配的行，输出由调用方决定。
+/// 只做普通的逐行查找，不支持 Config 中的上下文、排序、diff 等模式，
+/// 这些仍然由命令行（examples/minigrep.rs）使用的同步 `minigrep::run` 处理
+pub async fn run(
+    key: &str,
+    paths: &[String],
+    ignore_case: bool,
+) -> io::Result<Vec<(PathBuf, Vec<String>)>> {
+    // 遍历目录是阻塞操作，放到专门的线程池中执行；这里的 Cancel 不会被取消，总是能拿到完整的文件列表
+    let paths = paths.to_vec();
+    let files = tokio::task::spawn_blocking(move || {
+        collect_files(&paths, &Cancel::default(), &mut Stats::default()).unwrap_or_default()
+    })
+    .await
+    .map_err(io::Error::other)?;
+
+    let mut results = Vec::with_capacity(files.len());
+    for path in files {
+        let lines: Vec<String> = search_file(key, &path, ignore_case)
+            .await?
+            .try_collect()
+            .await?;
+        results.push((path, lines));
+    }
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+    use std::pin::Pin;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::task::{Context, Poll};
+
+    use futures::{StreamExt, TryStreamExt};
+    use tokio::io::{AsyncRead, ReadBuf};
+
+    use super::*;
+
+    // 每次 read 只返回一行，并记录一共被读了多少次
+    struct CountingReader {
+        lines: VecDeque<&'static str>,
+        reads: Arc<AtomicUsize>,
+    }
+
+    impl AsyncRead for CountingReader {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            _: &mut Context<'_>,
+            buf: &mut ReadBuf<'_>,
+        ) -> Poll<io::Result<()>> {
+            self.reads.fetch_add(1, Ordering::SeqCst);
+            if let Some(line) = self.lines.pop_front() {
+                buf.put_slice(line.as_bytes());
+            }
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    fn counting_reader(reads: &Arc<AtomicUsize>) -> BufReader<CountingReader> {
+        BufReader::new(CountingReader {
+            lines: VecDeque::from(["rust 1\n", "rust 2\n", "rust 3\n"]),
+            reads: reads.clone(),
+        })
+    }
+
+    #[tokio::test]
+    async fn case_sensitive() {
+        let query = "duct";
+        let contents = "\
+Rust:
+safe, fast, productive.
+Pick three.
+Duct tape.";
+
+        let results: Vec<String> = search_lines(query, contents.as_bytes(), false)
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(vec!["safe, fast, productive."], results);
+    }
+
+    #[tokio::test]
+    async fn case_insensitive() {
+        let query = "rUsT";
+        let contents = "\
+Rust:
+safe, fast, productive.
+Pick three.
+Trust me.";
+
+        let results: Vec<String> = search_lines(query, contents.as_bytes(), true)
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(vec!["Rust:", "Trust me."], results);
+    }
+
+    #[tokio::test]
+    async fn search_in_file() {
+        let stream = search_file("nobody", "src/examples/test.txt", false)
+            .await
+            .unwrap();
+        let results: Vec<String> = stream.try_collect().await.unwrap();
+        assert_eq!(
+            vec!["I'm nobody! Who are you?", "Are you nobody, too?"],
+            results
+        );
+
+        assert!(search_file("nobody", "src/examples/not_exist.txt", false)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn cancel_by_drop() {
+        // 读完所有内容需要 4 次 read：3 行加上最后表示结束的空读
+        let reads = Arc::new(AtomicUsize::new(0));
+        let results: Vec<String> = search_lines("rust", counting_reader(&reads), false)
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 3);
+        assert_eq!(reads.load(Ordering::SeqCst), 4);
+
+        let reads = Arc::new(AtomicUsize::new(0));
+        // unfold 生成的 Stream 没有实现 Unpin，调用 next 之前需要先 pin 住
+        let mut stream = Box::pin(search_lines("rust", counting_reader(&reads), false));
+        assert_eq!(reads.load(Ordering::SeqCst), 0);
+        assert_eq!(stream.next().await.unwrap().unwrap(), "rust 1");
+        assert_eq!(reads.load(Ordering::SeqCst), 1);
+
+        // 只取第一个结果，drop 之后剩下的行不再读取
+        drop(stream);
+        tokio::task::yield_now().await;
+        assert_eq!(reads.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn case_insensitive_non_ascii() {
+        // 与同步版本一样逐个字符比较小写形式
+        let contents = "Grüß Gott\nGRÜSS\n";
+        let results: Vec<String> = search_lines("GRÜß", contents.as_bytes(), true)
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(results, vec!["Grüß Gott"]);
+        assert!(matcher::is_match("GRÜß", "Grüß Gott", true));
+    }
+
+    #[tokio::test]
+    async fn run_files() {
+        let files = vec!["src/examples/test.txt".to_string()];
+        let results = run("nobody", &files, false).await.unwrap();
+        assert_eq!(
+            results,
+            vec![(
+                PathBuf::from("src/examples/test.txt"),
+                vec![
+                    "I'm nobody! Who are you?".to_string(),
+                    "Are you nobody, too?".to_string(),
+                ]
+            )]
+        );
+
+        // 目录会被展开，没有匹配的文件也会出现在结果中
+        let results = run("NOBODY", &["src/examples".to_string()], true)
+            .await
+            .unwrap();
+        let mut paths: Vec<_> = results.iter().map(|(path, _)| path.clone()).collect();
+        paths.sort();
+        assert_eq!(
+            paths,
+            vec![
+                PathBuf::from("src/examples/minigrep.rs"),
+                PathBuf::from("src/examples/test.txt"),
+            ]
+        );
+        let found = results
+            .iter()
+            .find(|(path, _)| path.ends_with("test.txt"))
+            .unwrap();
+        assert_eq!(found.1.len(), 2);
+
+        let files = vec!["src/examples/not_exist.txt".to_string()];
+        assert!(run("nobody", &files, false).await.is_err());
+    }
+}
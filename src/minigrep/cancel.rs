@@ -0,0 +1,106 @@
+/// 取消搜索：Cancel 可以克隆后交给其他线程，任意一个克隆调用 cancel 后，所有的克隆都会看到取消状态；
+/// 也可以设置一个截止时间（--timeout），超过截止时间后同样视为已经取消。
+/// 搜索循环每隔一段时间检查一次，发现取消后停止并返回已经找到的结果
+use std::error::Error;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// 逐行搜索时每隔多少行检查一次是否已经取消
+pub const CHECK_INTERVAL: usize = 1024;
+
+#[derive(Debug, Clone, Default)]
+pub struct Cancel {
+    cancelled: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+}
+
+impl Cancel {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// 返回一个共享取消状态、并且在 deadline 之后视为已经取消的克隆
+    pub fn with_deadline(&self, deadline: Instant) -> Cancel {
+        Cancel {
+            cancelled: Arc::clone(&self.cancelled),
+            deadline: Some(self.deadline.map_or(deadline, |d| d.min(deadline))),
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+            || self
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+}
+
+/// 搜索被取消或者超时时 run 返回的错误，此时已经输出了部分结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Search was cut short by a timeout or cancellation")
+    }
+}
+
+impl Error for Cancelled {}
+
+/// 解析 --timeout 的值：`1.5` 和 `1.5s` 为秒，`200ms` 为毫秒
+pub fn parse_timeout(s: &str) -> Result<Duration, &'static str> {
+    let (number, scale) = match s.strip_suffix("ms") {
+        Some(ms) => (ms, 0.001),
+        None => (s.strip_suffix('s').unwrap_or(s), 1.0),
+    };
+    number
+        .parse::<f64>()
+        .ok()
+        .and_then(|n| Duration::try_from_secs_f64(n * scale).ok())
+        .ok_or("Invalid timeout")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn cancel_from_another_thread() {
+        let cancel = Cancel::new();
+        let timed = cancel.with_deadline(Instant::now() + Duration::from_secs(60));
+        assert!(!timed.is_cancelled());
+
+        let handle = cancel.clone();
+        thread::spawn(move || handle.cancel()).join().unwrap();
+        assert!(cancel.is_cancelled());
+        assert!(timed.is_cancelled());
+    }
+
+    #[test]
+    fn deadline() {
+        let cancel = Cancel::new();
+        assert!(cancel.with_deadline(Instant::now()).is_cancelled());
+        // 多次设置截止时间时以较早的为准
+        let early = cancel
+            .with_deadline(Instant::now())
+            .with_deadline(Instant::now() + Duration::from_secs(60));
+        assert!(early.is_cancelled());
+        assert!(!cancel.is_cancelled());
+    }
+
+    #[test]
+    fn parse_timeout_values() {
+        assert_eq!(parse_timeout("2"), Ok(Duration::from_secs(2)));
+        assert_eq!(parse_timeout("1.5s"), Ok(Duration::from_millis(1500)));
+        assert_eq!(parse_timeout("200ms"), Ok(Duration::from_millis(200)));
+        assert!(parse_timeout("-1").is_err());
+        assert!(parse_timeout("soon").is_err());
+    }
+}
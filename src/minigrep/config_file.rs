@@ -0,0 +1,332 @@
+/// 配置文件：把常用的选项写进 `.minigrep.toml`，不必每次都在命令行中输入。
+/// 先读取用户目录下的 `~/.minigrep.toml`，再从当前目录开始逐级向上查找项目中的 `.minigrep.toml`，
+/// 项目配置覆盖用户配置。文件是标准的 TOML，由 toml crate 解析：
+///
+/// ```toml
+/// # 所有搜索的默认值
+/// context = 1
+/// format = "json"
+///
+/// [presets]
+/// errors = { pattern = "error", ignore_case = true, context = 2 }
+///
+/// [presets.todo]
+/// pattern = "TODO"
+/// only = "comment"
+/// ```
+///
+/// 用 `--preset errors` 使用预设，预设中的值覆盖文件顶层的默认值。
+/// 同一个选项的优先级从高到低为：命令行选项 > 环境变量（IGNORE_CASE）> 预设 > 文件中的默认值
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::de::{Deserializer, Error as _};
+use serde::Deserialize;
+
+use super::output::Format;
+use super::rust_lexer::{self, Class};
+
+pub const FILE_NAME: &str = ".minigrep.toml";
+
+/// 配置文件中可以设置的选项，None 表示没有设置
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Options {
+    // 只能在预设中设置，使用预设时所有的位置参数都是文件
+    pub pattern: Option<String>,
+    pub ignore_case: Option<bool>,
+    pub context: Option<usize>,
+    pub max_count: Option<usize>,
+    pub only_matching: Option<bool>,
+    #[serde(default, deserialize_with = "format")]
+    pub format: Option<Format>,
+    pub stats: Option<bool>,
+    // 文件中和命令行一样写作 only = "comment,string"
+    #[serde(default, rename = "only", deserialize_with = "classes")]
+    pub syntax: Option<Vec<Class>>,
+}
+
+fn format<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Format>, D::Error> {
+    match String::deserialize(deserializer)?.as_str() {
+        "text" => Ok(Some(Format::Text)),
+        "json" => Ok(Some(Format::Json)),
+        other => Err(D::Error::custom(format!("unknown format \"{}\"", other))),
+    }
+}
+
+fn classes<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Vec<Class>>, D::Error> {
+    let list = String::deserialize(deserializer)?;
+    rust_lexer::parse_classes(&list)
+        .map(Some)
+        .map_err(D::Error::custom)
+}
+
+impl Options {
+    /// self 中没有设置的选项使用 other 中的值
+    pub fn or(self, other: Options) -> Options {
+        Options {
+            pattern: self.pattern.or(other.pattern),
+            ignore_case: self.ignore_case.or(other.ignore_case),
+            context: self.context.or(other.context),
+            max_count: self.max_count.or(other.max_count),
+            only_matching: self.only_matching.or(other.only_matching),
+            format: self.format.or(other.format),
+            stats: self.stats.or(other.stats),
+            syntax: self.syntax.or(other.syntax),
+        }
+    }
+}
+
+/// 一个或多个配置文件合并后的内容
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Settings {
+    pub defaults: Options,
+    pub presets: HashMap<String, Options>,
+}
+
+impl Settings {
+    /// 从 $HOME 和当前目录查找配置文件，见 discover_from
+    pub fn discover() -> Result<Settings, &'static str> {
+        let home = env::var_os("HOME").map(PathBuf::from);
+        let cwd = env::current_dir().ok();
+        Settings::discover_from(home.as_deref(), cwd.as_deref())
+    }
+
+    /// 按顺序读取 home 下的用户配置和从 cwd 开始向上找到的项目配置，后读取的覆盖先读取的；
+    /// 格式错误时在 stderr 输出具体的原因
+    pub fn discover_from(
+        home: Option<&Path>,
+        cwd: Option<&Path>,
+    ) -> Result<Settings, &'static str> {
+        let project = cwd.and_then(|dir| find_up(dir, FILE_NAME));
+
+        // 在用户目录下运行时，项目配置就是用户配置，不需要读两遍
+        let mut paths: Vec<PathBuf> = home.map(|home| home.join(FILE_NAME)).into_iter().collect();
+        if let Some(project) = project {
+            if !paths.contains(&project) {
+                paths.push(project);
+            }
+        }
+
+        let mut settings = Settings::default();
+        for path in &paths {
+            let text = match fs::read_to_string(path) {
+                Ok(text) => text,
+                Err(_) => continue,
+            };
+            match Settings::parse(&text) {
+                Ok(file) => settings = file.or(settings),
+                Err(err) => {
+                    eprintln!("Invalid config file [{}]: {}", path.display(), err);
+                    return Err("Invalid config file");
+                }
+            }
+        }
+        Ok(settings)
+    }
+
+    /// self 中的默认值和预设覆盖 other 中的
+    pub fn or(self, other: Settings) -> Settings {
+        let mut presets = other.presets;
+        for (name, preset) in self.presets {
+            let preset = match presets.remove(&name) {
+                Some(other) => preset.or(other),
+                None => preset,
+            };
+            presets.insert(name, preset);
+        }
+        Settings {
+            defaults: self.defaults.or(other.defaults),
+            presets,
+        }
+    }
+
+    /// 得到使用 preset（如果有）时生效的选项
+    pub fn resolve(&self, preset: Option<&str>) -> Result<Options, &'static str> {
+        let defaults = self.defaults.clone();
+        match preset {
+            Some(name) => match self.presets.get(name) {
+                Some(preset) => Ok(preset.clone().or(defaults)),
+                None => Err("No such preset in config file"),
+            },
+            None => Ok(defaults),
+        }
+    }
+
+    /// 顶层是所有搜索的默认值，[presets] 表中的每一项是一个预设
+    pub fn parse(text: &str) -> Result<Settings, String> {
+        let mut table: toml::Table = text
+            .parse()
+            .map_err(|err: toml::de::Error| err.to_string())?;
+        let presets = match table.remove("presets") {
+            Some(presets) => presets
+                .try_into()
+                .map_err(|err: toml::de::Error| format!("in [presets]: {}", err))?,
+            None => HashMap::new(),
+        };
+        let defaults: Options = toml::Value::Table(table)
+            .try_into()
+            .map_err(|err: toml::de::Error| err.to_string())?;
+        if defaults.pattern.is_some() {
+            return Err("pattern can only be set in a preset".to_string());
+        }
+        Ok(Settings { defaults, presets })
+    }
+}
+
+// 从 dir 开始逐级向上查找名为 name 的文件
+fn find_up(dir: &Path, name: &str) -> Option<PathBuf> {
+    dir.ancestors()
+        .map(|dir| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILE: &str = r#"
+# 默认值
+context = 1
+format = "json"
+
+[presets]
+errors = { pattern = "error", ignore_case = true, context = 2 }  # 行尾注释
+empty = {}
+
+[presets.todo]
+pattern = "TODO \"x\""
+only = "comment"
+"#;
+
+    #[test]
+    fn parse_file() {
+        let settings = Settings::parse(FILE).unwrap();
+        assert_eq!(settings.defaults.context, Some(1));
+        assert_eq!(settings.defaults.format, Some(Format::Json));
+        assert_eq!(settings.presets.len(), 3);
+
+        let errors = &settings.presets["errors"];
+        assert_eq!(errors.pattern.as_deref(), Some("error"));
+        assert_eq!(errors.ignore_case, Some(true));
+        assert_eq!(errors.context, Some(2));
+
+        let todo = &settings.presets["todo"];
+        assert_eq!(todo.pattern.as_deref(), Some("TODO \"x\""));
+        assert_eq!(todo.syntax, Some(vec![Class::Comment]));
+    }
+
+    #[test]
+    fn resolve_preset() {
+        let settings = Settings::parse(FILE).unwrap();
+        // 预设覆盖默认值，预设中没有的选项使用默认值
+        let options = settings.resolve(Some("errors")).unwrap();
+        assert_eq!(options.context, Some(2));
+        assert_eq!(options.format, Some(Format::Json));
+        assert_eq!(settings.resolve(Some("empty")).unwrap().context, Some(1));
+        assert!(settings.resolve(Some("none")).is_err());
+        assert_eq!(settings.resolve(None).unwrap().pattern, None);
+    }
+
+    #[test]
+    fn project_over_user() {
+        let user =
+            Settings::parse("stats = true\ncontext = 1\n[presets.a]\npattern = \"x\"\ncontext = 3")
+                .unwrap();
+        let project = Settings::parse("context = 2\n[presets.a]\npattern = \"y\"").unwrap();
+        let settings = project.or(user);
+        assert_eq!(settings.defaults.stats, Some(true));
+        assert_eq!(settings.defaults.context, Some(2));
+        let a = &settings.presets["a"];
+        assert_eq!((a.pattern.as_deref(), a.context), (Some("y"), Some(3)));
+    }
+
+    #[test]
+    fn invalid_files() {
+        for text in [
+            "context = \"1\"",
+            "colour = true",
+            "[other]",
+            "context = 1 2",
+            "[presets]\nerrors = 1",
+            "pattern = \"x\"",
+            "pattern = \"x",
+            "[presets]\nerrors = { context = 1",
+        ] {
+            assert!(Settings::parse(text).is_err(), "{}", text);
+        }
+        // 错误信息中包含出错的位置
+        assert!(Settings::parse("\n\nstats = yes")
+            .unwrap_err()
+            .contains("line 3"));
+        assert!(Settings::parse("format = \"xml\"")
+            .unwrap_err()
+            .contains("unknown format \"xml\""));
+    }
+
+    #[test]
+    fn standard_toml() {
+        // 字面量字符串、Unicode 转义、点分键、带引号的键和数字中的下划线
+        let settings = Settings::parse(
+            r#"
+stats = true
+presets.quoted.pattern = 'C:\path'
+presets."with space" = { pattern = "tab\there\u0021", max_count = 1_000 }
+"#,
+        )
+        .unwrap();
+        assert_eq!(settings.defaults.stats, Some(true));
+        assert_eq!(
+            settings.presets["quoted"].pattern.as_deref(),
+            Some(r"C:\path")
+        );
+        let preset = &settings.presets["with space"];
+        assert_eq!(preset.pattern.as_deref(), Some("tab\there!"));
+        assert_eq!(preset.max_count, Some(1000));
+
+        // 数组等不支持的类型会报错，而不是被悄悄地当作别的值
+        assert!(Settings::parse("only = [\"comment\"]").is_err());
+        assert!(Settings::parse("context = -1").is_err());
+    }
+
+    #[test]
+    fn find_in_ancestors() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/minigrep");
+        assert_eq!(
+            find_up(&dir, "Cargo.toml"),
+            Some(Path::new(env!("CARGO_MANIFEST_DIR")).join("Cargo.toml"))
+        );
+        assert_eq!(find_up(&dir, "no-such-file.toml"), None);
+    }
+
+    #[test]
+    fn discover_from_home_and_project() {
+        // 在临时目录中构造 home 和项目，不受运行测试的机器上已有的配置文件影响
+        let root = env::temp_dir().join(format!("minigrep-config-{}", std::process::id()));
+        let home = root.join("home");
+        let project = root.join("project");
+        let cwd = project.join("src/nested");
+        fs::create_dir_all(&home).unwrap();
+        fs::create_dir_all(&cwd).unwrap();
+        fs::write(home.join(FILE_NAME), "context = 1\nstats = true").unwrap();
+        fs::write(project.join(FILE_NAME), "context = 2").unwrap();
+
+        let settings = Settings::discover_from(Some(&home), Some(&cwd)).unwrap();
+        assert_eq!(settings.defaults.context, Some(2));
+        assert_eq!(settings.defaults.stats, Some(true));
+
+        // 在 home 中运行时只读取一次用户配置
+        let settings = Settings::discover_from(Some(&home), Some(&home)).unwrap();
+        assert_eq!(settings.defaults.context, Some(1));
+        assert_eq!(
+            Settings::discover_from(None, None).unwrap(),
+            Settings::default()
+        );
+
+        fs::write(project.join(FILE_NAME), "context = ").unwrap();
+        assert!(Settings::discover_from(Some(&home), Some(&cwd)).is_err());
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,253 @@
+/// 按列搜索 CSV/TSV 数据：支持双引号包裹的字段（字段中可以包含分隔符、换行符，`""` 表示一个引号），
+/// 第一条记录作为表头，只在指定的列中查找 key，输出时保留表头
+use std::error::Error;
+use std::str::FromStr;
+
+use super::matcher;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Column {
+    // 按表头中的列名指定
+    Name(String),
+    // 按列号指定，从 1 开始，与 cut/awk 保持一致
+    Index(usize),
+}
+
+impl FromStr for Column {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.parse::<usize>() {
+            Ok(0) => Err("Column numbers start at 1"),
+            Ok(index) => Ok(Column::Index(index)),
+            Err(_) if s.is_empty() => Err("Empty column name"),
+            Err(_) => Ok(Column::Name(s.to_string())),
+        }
+    }
+}
+
+/// 解析 `name,2,height` 这样以逗号分隔的列列表
+pub fn parse_columns(list: &str) -> Result<Vec<Column>, &'static str> {
+    list.split(',').map(|col| col.trim().parse()).collect()
+}
+
+/// 将内容解析为记录列表，每条记录是一个字段列表
+pub fn parse(contents: &str, delimiter: char) -> Result<Vec<Vec<String>>, &'static str> {
+    records(contents, delimiter).collect()
+}
+
+/// 逐条解析记录，只在需要下一条记录时才继续读取内容，这样调用方可以随时停止
+pub fn records(contents: &str, delimiter: char) -> Records<'_> {
+    Records {
+        chars: contents.chars().peekable(),
+        delimiter,
+    }
+}
+
+pub struct Records<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    delimiter: char,
+}
+
+impl Iterator for Records<'_> {
+    type Item = Result<Vec<String>, &'static str>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut record = Vec::new();
+        let mut field = String::new();
+        // 当前字段是否在引号内
+        let mut quoted = false;
+
+        while let Some(c) = self.chars.next() {
+            if quoted {
+                match c {
+                    '"' if self.chars.peek() == Some(&'"') => {
+                        self.chars.next();
+                        field.push('"');
+                    }
+                    '"' => quoted = false,
+                    _ => field.push(c),
+                }
+                continue;
+            }
+
+            match c {
+                '"' if field.is_empty() => quoted = true,
+                '\r' if self.chars.peek() == Some(&'\n') => {}
+                '\n' => {
+                    record.push(std::mem::take(&mut field));
+                    // 忽略空行
+                    if record.len() > 1 || !record[0].is_empty() {
+                        return Some(Ok(record));
+                    }
+                    record.clear();
+                }
+                _ if c == self.delimiter => record.push(std::mem::take(&mut field)),
+                _ => field.push(c),
+            }
+        }
+
+        if quoted {
+            return Some(Err("Unterminated quoted field"));
+        }
+        if !field.is_empty() || !record.is_empty() {
+            record.push(field);
+            return Some(Ok(record));
+        }
+        None
+    }
+}
+
+/// 将一条记录重新编码成一行，字段中含有分隔符、引号或换行符时用引号包裹
+pub fn write_record(record: &[String], delimiter: char) -> String {
+    record
+        .iter()
+        .map(|field| {
+            if field.contains([delimiter, '"', '\n', '\r']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field.clone()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(&delimiter.to_string())
+}
+
+/// 只在 columns 指定的列中查找 key（columns 为空时查找所有列），
+/// 返回的第一条记录是表头，后面是最多 max_count 条匹配的记录，找够以后不再解析剩下的内容
+pub fn search_columns(
+    key: &str,
+    contents: &str,
+    delimiter: char,
+    columns: &[Column],
+    ignore_case: bool,
+    max_count: usize,
+) -> Result<Vec<Vec<String>>, Box<dyn Error>> {
+    let mut records = records(contents, delimiter);
+    let header = match records.next() {
+        Some(header) => header?,
+        None => return Ok(Vec::new()),
+    };
+
+    let mut indexes = Vec::with_capacity(columns.len());
+    for column in columns {
+        let index = match column {
+            Column::Name(name) => header.iter().position(|h| h == name),
+            Column::Index(index) => Some(index - 1).filter(|i| *i < header.len()),
+        };
+        match index {
+            Some(index) => indexes.push(index),
+            None => return Err(format!("No such column: {:?}", column).into()),
+        }
+    }
+    if indexes.is_empty() {
+        indexes = (0..header.len()).collect();
+    }
+
+    let is_match = |field: &String| matcher::is_match(key, field, ignore_case);
+
+    let mut results = vec![header];
+    for record in records {
+        if results.len() > max_count {
+            break;
+        }
+        let record = record?;
+        if indexes.iter().any(|i| record.get(*i).is_some_and(is_match)) {
+            results.push(record);
+        }
+    }
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(fields: &[&str]) -> Vec<String> {
+        fields.iter().map(|f| f.to_string()).collect()
+    }
+
+    const DATA: &str = "\
+name,height,note
+zhangsan,160,\"likes rust, go\"
+lisi,155,\"said \"\"hi\"\"\"
+wangwu,165,\"multi
+line\"
+";
+
+    #[test]
+    fn parse_quoted_fields() {
+        let records = parse(DATA, ',').unwrap();
+        assert_eq!(records.len(), 4);
+        assert_eq!(records[1], strings(&["zhangsan", "160", "likes rust, go"]));
+        assert_eq!(records[2], strings(&["lisi", "155", "said \"hi\""]));
+        assert_eq!(records[3], strings(&["wangwu", "165", "multi\nline"]));
+
+        assert_eq!(
+            parse("a\t\"b\tc\"\r\n", '\t').unwrap(),
+            vec![strings(&["a", "b\tc"])]
+        );
+        assert!(parse("a,\"b", ',').is_err());
+    }
+
+    #[test]
+    fn write_quoted_fields() {
+        let records = parse(DATA, ',').unwrap();
+        let lines: Vec<String> = records.iter().map(|r| write_record(r, ',')).collect();
+        assert_eq!(lines.join("\n") + "\n", DATA);
+    }
+
+    #[test]
+    fn search_named_column() {
+        let columns = parse_columns("note").unwrap();
+        let results = search_columns("rust", DATA, ',', &columns, false, usize::MAX).unwrap();
+        assert_eq!(
+            results,
+            vec![
+                strings(&["name", "height", "note"]),
+                strings(&["zhangsan", "160", "likes rust, go"]),
+            ]
+        );
+
+        // key 只出现在 name 列中，在 note 列中查找不到
+        let results = search_columns("wangwu", DATA, ',', &columns, false, usize::MAX).unwrap();
+        assert_eq!(results, vec![strings(&["name", "height", "note"])]);
+    }
+
+    #[test]
+    fn search_numbered_column() {
+        let columns = parse_columns("1, 2").unwrap();
+        assert_eq!(columns, vec![Column::Index(1), Column::Index(2)]);
+
+        let results = search_columns("LI", DATA, ',', &columns, true, usize::MAX).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[1][0], "lisi");
+
+        let results = search_columns("16", DATA, ',', &[], false, usize::MAX).unwrap();
+        assert_eq!(results.len(), 3);
+    }
+
+    #[test]
+    fn stop_at_max_count() {
+        // 找到一条匹配的记录以后就不再解析，后面未闭合的引号不会报错
+        let contents = "name\nrust\nrust\n\"unterminated";
+        let results = search_columns("rust", contents, ',', &[], false, 1).unwrap();
+        assert_eq!(results, vec![strings(&["name"]), strings(&["rust"])]);
+        assert_eq!(
+            search_columns("rust", contents, ',', &[], false, 0).unwrap(),
+            vec![strings(&["name"])]
+        );
+        assert!(search_columns("rust", contents, ',', &[], false, 3).is_err());
+
+        // 与其他搜索模式一样按字符比较大小写
+        let results = search_columns("GRÜSS", "a\ngrüß\ngrüss\n", ',', &[], true, 9).unwrap();
+        assert_eq!(results, vec![strings(&["a"]), strings(&["grüss"])]);
+    }
+
+    #[test]
+    fn unknown_column() {
+        assert!(parse_columns("0").is_err());
+        assert!(search_columns("a", DATA, ',', &[Column::Index(4)], false, 1).is_err());
+        assert!(search_columns("a", DATA, ',', &parse_columns("age").unwrap(), false, 1).is_err());
+    }
+}
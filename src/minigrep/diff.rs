@@ -0,0 +1,328 @@
+/// 补丁模式：读取 unified diff（文件或标准输入），只在新增和/或删除的行中查找 key，
+/// 结果带上目标文件的路径和在新文件中的行号，方便在代码评审时检查补丁引入的内容
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::str::FromStr;
+
+use super::output::{json_string, Format};
+use super::stats::Stats;
+use super::{cancel, matcher, Config};
+
+/// 在补丁中的哪些行里查找
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Added,
+    Removed,
+    Both,
+}
+
+impl FromStr for Side {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "added" => Ok(Side::Added),
+            "removed" => Ok(Side::Removed),
+            "both" => Ok(Side::Both),
+            _ => Err("Diff side must be added, removed or both"),
+        }
+    }
+}
+
+impl Side {
+    fn accepts(self, added: bool) -> bool {
+        match self {
+            Side::Added => added,
+            Side::Removed => !added,
+            Side::Both => true,
+        }
+    }
+}
+
+/// 补丁中匹配的一行
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffMatch {
+    // 目标文件的路径；文件被删除时（目标为 /dev/null）使用原文件的路径
+    pub path: String,
+    // 新增的行是它在新文件中的行号；删除的行在新文件中已经不存在，是它被删除的位置，
+    // 也就是新文件中紧随其后的那一行的行号
+    pub line: usize,
+    pub added: bool,
+    pub text: String,
+}
+
+impl DiffMatch {
+    pub fn to_text(&self) -> String {
+        let sign = if self.added { '+' } else { '-' };
+        format!("{}:{}:{}{}", self.path, self.line, sign, self.text)
+    }
+
+    pub fn to_json(&self) -> String {
+        format!(
+            "{{\"path\":{},\"line\":{},\"side\":\"{}\",\"text\":{}}}",
+            json_string(&self.path),
+            self.line,
+            if self.added { "added" } else { "removed" },
+            json_string(&self.text)
+        )
+    }
+}
+
+// 去掉 --- 和 +++ 行中 git 加上的 a/ b/ 前缀，以及 diff -u 在制表符后面加上的时间戳
+fn header_path(header: &str) -> String {
+    let path = header.split('\t').next().unwrap_or(header).trim_end();
+    path.strip_prefix("a/")
+        .or_else(|| path.strip_prefix("b/"))
+        .unwrap_or(path)
+        .to_string()
+}
+
+// 解析 hunk 头 `@@ -old_start,old_len +new_start,new_len @@`，省略长度时长度为 1
+fn parse_hunk(header: &str) -> Option<(usize, usize, usize, usize)> {
+    let mut ranges = header.strip_prefix("@@ ")?.split_whitespace();
+    let range = |range: Option<&str>, sign: char| -> Option<(usize, usize)> {
+        let range = range?.strip_prefix(sign)?;
+        match range.split_once(',') {
+            Some((start, len)) => Some((start.parse().ok()?, len.parse().ok()?)),
+            None => Some((range.parse().ok()?, 1)),
+        }
+    };
+    let (old_start, old_len) = range(ranges.next(), '-')?;
+    let (new_start, new_len) = range(ranges.next(), '+')?;
+    Some((old_start, old_len, new_start, new_len))
+}
+
+/// 逐行读取补丁，在 config.diff 指定的行中查找 config.key，匹配的行数达到 max_count 后停止读取
+pub fn search_diff<R: BufRead>(
+    config: &Config,
+    reader: R,
+    stats: &mut Stats,
+) -> io::Result<Vec<DiffMatch>> {
+    let side = config.diff.unwrap_or(Side::Added);
+    let max_count = config.max_count.unwrap_or(usize::MAX);
+    let mut results = Vec::new();
+    if max_count == 0 {
+        return Ok(results);
+    }
+
+    let mut old_path = String::new();
+    let mut new_path = String::new();
+    // 当前 hunk 中还剩多少行旧内容和新内容，都为 0 时不在 hunk 中
+    let mut old_left = 0;
+    let mut new_left = 0;
+    let mut new_line = 0;
+    for (i, line) in reader.lines().enumerate() {
+        if i % cancel::CHECK_INTERVAL == 0 && super::cancelled(config, stats) {
+            break;
+        }
+        let line = line?;
+        stats.bytes_scanned += line.len() + 1;
+        stats.lines_scanned += 1;
+        let line = line.strip_suffix('\r').unwrap_or(&line);
+
+        if old_left == 0 && new_left == 0 {
+            // hunk 之外只关心文件头和 hunk 头，其余的（diff --git、index 等）都忽略
+            if let Some(header) = line.strip_prefix("--- ") {
+                old_path = header_path(header);
+            } else if let Some(header) = line.strip_prefix("+++ ") {
+                new_path = header_path(header);
+            } else if let Some((_, old_len, new_start, new_len)) = parse_hunk(line) {
+                old_left = old_len;
+                new_left = new_len;
+                new_line = new_start;
+            }
+            continue;
+        }
+
+        let (added, text) = match line.chars().next() {
+            Some('+') => (true, &line[1..]),
+            Some('-') => (false, &line[1..]),
+            // `\ No newline at end of file` 不占行号
+            Some('\\') => continue,
+            // 上下文行，空行是去掉了行首空格的上下文行
+            _ => {
+                old_left = old_left.saturating_sub(1);
+                new_left = new_left.saturating_sub(1);
+                new_line += 1;
+                continue;
+            }
+        };
+        let line_number = new_line;
+        if added {
+            new_left = new_left.saturating_sub(1);
+            new_line += 1;
+        } else {
+            old_left = old_left.saturating_sub(1);
+        }
+
+        if !side.accepts(added) || !matcher::is_match(&config.key, text, config.ignore_case) {
+            continue;
+        }
+        stats.matched_lines += 1;
+        stats.matches += matcher::find_iter(&config.key, text, config.ignore_case)
+            .count()
+            .max(1);
+        let path = if new_path == "/dev/null" {
+            &old_path
+        } else {
+            &new_path
+        };
+        results.push(DiffMatch {
+            path: path.clone(),
+            line: line_number,
+            added,
+            text: text.to_string(),
+        });
+        if results.len() == max_count {
+            break;
+        }
+    }
+    Ok(results)
+}
+
+/// 搜索 config.files 中的补丁，没有给出文件或者文件为 `-` 时读取标准输入
+pub fn run(config: &Config, stats: &mut Stats) -> Result<(), Box<dyn Error>> {
+    let stdin = ["-".to_string()];
+    let inputs = if config.files.is_empty() {
+        &stdin[..]
+    } else {
+        &config.files[..]
+    };
+
+    for input in inputs {
+        if super::cancelled(config, stats) {
+            break;
+        }
+        let found = if input == "-" {
+            search_diff(config, io::stdin().lock(), stats)
+        } else {
+            File::open(input).and_then(|file| search_diff(config, BufReader::new(file), stats))
+        };
+        let found = match found {
+            Ok(found) => found,
+            Err(err) => {
+                stats.files_skipped += 1;
+                eprintln!("Skipped [{}]: {}", input, err);
+                continue;
+            }
+        };
+        stats.files_searched += 1;
+
+        for m in found {
+            match config.format {
+                Format::Text => println!("{}", m.to_text()),
+                Format::Json => println!("{}", m.to_json()),
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PATCH: &str = "\
+diff --git a/src/lib.rs b/src/lib.rs
+index 1111111..2222222 100644
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,4 +1,5 @@
+ fn main() {
+-    let x = a.unwrap();
++    let x = a?;
++    let y = b.unwrap();
+
+     run(x, y);
+@@ -10,2 +11,2 @@ fn other() {
+--- let s = c.unwrap();
++-- let s = c.expect(\"c\");
+ }
+--- a/old.rs
++++ /dev/null
+@@ -1 +0,0 @@
+-old.unwrap()
+\\ No newline at end of file
+";
+
+    fn search(key: &str, side: Side) -> Vec<DiffMatch> {
+        let config = Config {
+            key: key.to_string(),
+            diff: Some(side),
+            ..Default::default()
+        };
+        search_diff(&config, PATCH.as_bytes(), &mut Stats::default()).unwrap()
+    }
+
+    #[test]
+    fn added_lines() {
+        let found = search("unwrap", Side::Added);
+        assert_eq!(
+            found,
+            vec![DiffMatch {
+                path: "src/lib.rs".to_string(),
+                line: 3,
+                added: true,
+                text: "    let y = b.unwrap();".to_string(),
+            }]
+        );
+        assert_eq!(found[0].to_text(), "src/lib.rs:3:+    let y = b.unwrap();");
+        assert_eq!(
+            found[0].to_json(),
+            r#"{"path":"src/lib.rs","line":3,"side":"added","text":"    let y = b.unwrap();"}"#
+        );
+
+        // hunk 中以 -- 开头的删除行不会被当作文件头
+        let found = search("expect", Side::Added);
+        assert_eq!(
+            (found[0].line, found[0].text.as_str()),
+            (11, "-- let s = c.expect(\"c\");")
+        );
+    }
+
+    #[test]
+    fn removed_lines() {
+        let found: Vec<String> = search("unwrap", Side::Removed)
+            .iter()
+            .map(DiffMatch::to_text)
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                "src/lib.rs:2:-    let x = a.unwrap();",
+                "src/lib.rs:11:--- let s = c.unwrap();",
+                "old.rs:0:-old.unwrap()",
+            ]
+        );
+        assert_eq!(search("unwrap", Side::Both).len(), 4);
+    }
+
+    #[test]
+    fn max_count_and_stats() {
+        let config = Config {
+            key: "UNWRAP".to_string(),
+            ignore_case: true,
+            max_count: Some(2),
+            diff: Some(Side::Both),
+            ..Default::default()
+        };
+        let mut stats = Stats::default();
+        let found = search_diff(&config, PATCH.as_bytes(), &mut stats).unwrap();
+        assert_eq!(found.len(), 2);
+        assert_eq!((stats.matched_lines, stats.matches), (2, 2));
+        // 第二个匹配在第 9 行，之后不再读取
+        assert_eq!(stats.lines_scanned, 9);
+    }
+
+    #[test]
+    fn hunk_header() {
+        assert_eq!(parse_hunk("@@ -10,2 +11,3 @@ fn f()"), Some((10, 2, 11, 3)));
+        assert_eq!(parse_hunk("@@ -1 +1 @@"), Some((1, 1, 1, 1)));
+        assert_eq!(parse_hunk("@@ x @@"), None);
+        assert_eq!(
+            header_path("a/src/main.rs\t2024-01-01 00:00"),
+            "src/main.rs"
+        );
+    }
+}
@@ -0,0 +1,196 @@
+// 快速路径：不再逐行调用 contains，而是用 memchr 中 SIMD 加速的 memmem 在整个缓冲区中查找 key，
+// 找到以后再向两边查找换行符确定所在的行，接着从下一行开始继续查找。
+// 没有匹配的行完全不需要逐行处理，匹配很少的大文件（比如日志）可以快很多
+use memchr::memmem::Finder;
+use memchr::{memchr, memchr_iter, memrchr};
+
+use super::stats::Stats;
+use super::Config;
+
+/// key 能否用快速路径查找：按行匹配时 key 不能跨行，空的 key 匹配所有的行，这些情况交给逐行的实现
+pub fn usable(key: &str) -> bool {
+    !key.is_empty() && !key.contains(['\n', '\r'])
+}
+
+/// config 中的搜索能否走快速路径：只支持区分大小写、没有上下文、没有语法过滤的普通搜索
+pub fn applies(config: &Config) -> bool {
+    !config.ignore_case && config.context == 0 && config.syntax.is_empty() && usable(&config.key)
+}
+
+/// 依次返回包含 key 的行：(行的起始位置, 行的结束位置（不含换行符）, 行的内容（去掉了行尾的 \r）)
+pub struct MatchedLines<'k, 'a> {
+    finder: Finder<'k>,
+    contents: &'a str,
+    pos: usize,
+}
+
+impl<'k, 'a> MatchedLines<'k, 'a> {
+    pub fn new(key: &'k str, contents: &'a str) -> Self {
+        MatchedLines {
+            finder: Finder::new(key),
+            contents,
+            pos: 0,
+        }
+    }
+}
+
+impl<'k, 'a> Iterator for MatchedLines<'k, 'a> {
+    type Item = (usize, usize, &'a str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let bytes = self.contents.as_bytes();
+        let hit = self.pos + self.finder.find(&bytes[self.pos..])?;
+        // pos 总是某一行的开头，所以只需要在 [pos, hit) 中向前查找换行符
+        let start = memrchr(b'\n', &bytes[self.pos..hit]).map_or(self.pos, |i| self.pos + i + 1);
+        let end = memchr(b'\n', &bytes[hit..]).map_or(bytes.len(), |i| hit + i);
+        self.pos = (end + 1).min(bytes.len());
+
+        let line = &self.contents[start..end];
+        Some((start, end, line.strip_suffix('\r').unwrap_or(line)))
+    }
+}
+
+/// 与 `minigrep::search` 的结果相同
+pub fn search<'a>(key: &str, contents: &'a str) -> Vec<&'a str> {
+    if !usable(key) {
+        return super::search(key, contents);
+    }
+    MatchedLines::new(key, contents)
+        .map(|(_, _, line)| line)
+        .collect()
+}
+
+/// 用快速路径完成 search_reader 的工作，调用前需要先用 applies 检查 config。
+/// 统计信息与 search_reader 一致：达到 max_count 后剩余的内容不计入扫描的字节数和行数
+pub fn search_lines(config: &Config, contents: &str, stats: &mut Stats) -> Vec<String> {
+    let mut results = Vec::new();
+    let max_count = config.max_count.unwrap_or(usize::MAX);
+    if max_count == 0 || super::cancelled(config, stats) {
+        return results;
+    }
+
+    let finder = Finder::new(&config.key);
+    let mut matched_lines = 0;
+    let mut scanned = contents.len();
+    for (start, end, line) in MatchedLines::new(&config.key, contents) {
+        // 没有匹配的内容 memmem 扫描得很快，只需要在每次匹配时检查是否已经取消
+        if super::cancelled(config, stats) {
+            scanned = start;
+            break;
+        }
+        let matches = finder.find_iter(line.as_bytes()).count();
+        stats.matches += matches;
+        stats.matched_lines += 1;
+        if config.only_matching {
+            // 区分大小写时匹配到的子串就是 key 本身
+            results.extend(std::iter::repeat_n(config.key.clone(), matches));
+        } else {
+            results.push(line.to_string());
+        }
+
+        matched_lines += 1;
+        if matched_lines == max_count {
+            scanned = (end + 1).min(contents.len());
+            break;
+        }
+    }
+
+    let scanned = &contents.as_bytes()[..scanned];
+    stats.bytes_scanned += scanned.len();
+    stats.lines_scanned += memchr_iter(b'\n', scanned).count();
+    // 最后一行没有换行符时也算一行
+    if scanned.last().is_some_and(|&b| b != b'\n') {
+        stats.lines_scanned += 1;
+    }
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::minigrep::search_reader;
+
+    const CONTENTS: &str = "\
+rust 1\r
+go
+rust rust 3
+rus
+t
+
+last rust";
+
+    #[test]
+    fn same_as_search() {
+        for key in [
+            "rust",
+            "r",
+            "go",
+            "st 1",
+            "t\nrust",
+            "",
+            "java",
+            "last rust",
+        ] {
+            assert_eq!(
+                search(key, CONTENTS),
+                crate::minigrep::search(key, CONTENTS),
+                "{:?}",
+                key
+            );
+        }
+        assert_eq!(
+            search("rust", CONTENTS),
+            vec!["rust 1", "rust rust 3", "last rust"]
+        );
+    }
+
+    #[test]
+    fn same_as_search_reader() {
+        let configs = [
+            Config {
+                key: "rust".to_string(),
+                ..Default::default()
+            },
+            Config {
+                key: "rust".to_string(),
+                only_matching: true,
+                ..Default::default()
+            },
+            Config {
+                key: "rust".to_string(),
+                max_count: Some(2),
+                ..Default::default()
+            },
+            Config {
+                key: "go".to_string(),
+                max_count: Some(1),
+                ..Default::default()
+            },
+            Config {
+                key: "java".to_string(),
+                ..Default::default()
+            },
+        ];
+        for config in configs {
+            assert!(applies(&config));
+            let mut expected_stats = Stats::default();
+            let expected =
+                search_reader(&config, CONTENTS.as_bytes(), &mut expected_stats).unwrap();
+            let mut stats = Stats::default();
+            assert_eq!(search_lines(&config, CONTENTS, &mut stats), expected);
+            assert_eq!(stats, expected_stats, "{:?}", config);
+        }
+    }
+
+    #[test]
+    fn fallback() {
+        let config = Config {
+            key: "rust".to_string(),
+            ignore_case: true,
+            ..Default::default()
+        };
+        assert!(!applies(&config));
+        assert!(!usable(""));
+        assert!(!usable("a\nb"));
+    }
+}
@@ -0,0 +1,93 @@
+// 各种搜索模式共用的匹配逻辑：返回匹配在原文中的字节区间，而不仅仅是“是否包含”
+
+/// 从 from 开始查找 key，返回第一次匹配的字节区间 [start, end)
+pub fn find(key: &str, haystack: &str, from: usize, ignore_case: bool) -> Option<(usize, usize)> {
+    if key.is_empty() {
+        return None;
+    }
+
+    let rest = &haystack[from..];
+    if !ignore_case {
+        return rest.find(key).map(|i| (from + i, from + i + key.len()));
+    }
+
+    // 逐个字符比较小写形式，这样得到的位置始终落在原文的字符边界上
+    rest.char_indices().find_map(|(i, _)| {
+        let mut chars = rest[i..].char_indices();
+        for k in key.chars() {
+            let (_, c) = chars.next()?;
+            if !c.to_lowercase().eq(k.to_lowercase()) {
+                return None;
+            }
+        }
+        let len = chars.next().map_or(rest.len() - i, |(j, _)| j);
+        Some((from + i, from + i + len))
+    })
+}
+
+/// 依次返回所有互不重叠的匹配区间
+pub fn find_iter<'a>(
+    key: &'a str,
+    haystack: &'a str,
+    ignore_case: bool,
+) -> impl Iterator<Item = (usize, usize)> + 'a {
+    let mut pos = 0;
+    std::iter::from_fn(move || {
+        let (start, end) = find(key, haystack, pos, ignore_case)?;
+        pos = end;
+        Some((start, end))
+    })
+}
+
+/// 忽略大小写时用来比较的形式：逐个字符转换成小写，与 find 比较字符的方式一致。
+/// 需要把文本当作键（比如建立索引）时用它，这样与其他搜索模式的大小写规则相同
+pub fn fold_case(text: &str) -> String {
+    text.chars().flat_map(char::to_lowercase).collect()
+}
+
+/// 与 `str::contains` 一致，空的 key 可以匹配任意内容
+pub fn is_match(key: &str, haystack: &str, ignore_case: bool) -> bool {
+    key.is_empty() || find(key, haystack, 0, ignore_case).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_all_matches() {
+        let line = "Rust is safe, rust is fast, RUST!";
+        let found: Vec<_> = find_iter("rust", line, false).collect();
+        assert_eq!(found, vec![(14, 18)]);
+
+        let found: Vec<&str> = find_iter("rust", line, true)
+            .map(|(start, end)| &line[start..end])
+            .collect();
+        assert_eq!(found, vec!["Rust", "rust", "RUST"]);
+    }
+
+    #[test]
+    fn find_non_ascii() {
+        let line = "Grüß Gott! GRÜß";
+        let found: Vec<&str> = find_iter("grüß", line, true)
+            .map(|(start, end)| &line[start..end])
+            .collect();
+        assert_eq!(found, vec!["Grüß", "GRÜß"]);
+    }
+
+    #[test]
+    fn empty_key() {
+        assert_eq!(find_iter("", "abc", false).count(), 0);
+        assert!(is_match("", "abc", false));
+        assert!(!is_match("d", "abc", true));
+    }
+
+    #[test]
+    fn fold_like_find() {
+        // str::to_lowercase 会把词尾的 Σ 变成 ς，逐个字符转换时始终是 σ，与 find 的比较方式相同
+        assert_eq!(fold_case("ΟΔΟΣ"), "οδοσ");
+        assert_ne!("ΟΔΟΣ".to_lowercase(), fold_case("ΟΔΟΣ"));
+        assert!(is_match("ΟΔΟΣ", "οδοσ", true));
+        assert_eq!(fold_case("Grüß"), "grüß");
+    }
+}
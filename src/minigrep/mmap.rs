@@ -0,0 +1,55 @@
+// 大文件的内存映射输入：文件超过阈值时直接映射到内存中搜索，不再复制一份到 String，
+// 映射得到的内容和读入内存的内容使用同一套匹配逻辑（search_contents）。
+// 管道、设备等特殊文件无法映射，映射失败时也不报错，调用方退回到普通的缓冲读取。
+// 默认不使用，只有给出 --mmap-threshold 时才映射，服务模式从不映射
+use std::fs::File;
+use std::io;
+
+use memmap2::Mmap;
+
+/// 文件是普通文件并且不小于 threshold 字节时映射到内存，否则返回 None
+pub fn map(file: &File, threshold: u64) -> Option<Mmap> {
+    let metadata = file.metadata().ok()?;
+    // 空文件无法映射，也没有必要映射
+    if !metadata.is_file() || metadata.len() == 0 || metadata.len() < threshold {
+        return None;
+    }
+    // SAFETY: 映射期间文件被其他进程截断或修改时，读取映射的内存是未定义行为。
+    // 这条路径针对的是很少变化的大文件（比如归档的日志），并且映射只在一次搜索期间存在
+    unsafe { Mmap::map(file) }.ok()
+}
+
+/// 把映射的内容当作 UTF-8 文本，不是合法的 UTF-8 时与 read_to_string 返回同样类型的错误
+pub fn as_str(map: &Mmap) -> io::Result<&str> {
+    std::str::from_utf8(map).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn map_above_threshold() {
+        let path = std::env::temp_dir().join(format!("minigrep-mmap-{}.txt", std::process::id()));
+        fs::write(&path, "rust\ngo\n").unwrap();
+        let file = File::open(&path).unwrap();
+
+        let map = map(&file, 8).unwrap();
+        assert_eq!(as_str(&map).unwrap(), "rust\ngo\n");
+        assert!(super::map(&file, 9).is_none());
+
+        fs::write(&path, b"\xff\xfe").unwrap();
+        let file = File::open(&path).unwrap();
+        assert!(as_str(&super::map(&file, 0).unwrap()).is_err());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn special_files_are_not_mapped() {
+        // 字符设备不是普通文件
+        let file = File::open("/dev/null").unwrap();
+        assert!(map(&file, 0).is_none());
+    }
+}
@@ -0,0 +1,1324 @@
+use std::collections::VecDeque;
+use std::env;
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+pub mod async_search;
+pub mod cancel;
+pub mod config_file;
+pub mod delimited;
+pub mod diff;
+pub mod fast;
+pub mod matcher;
+pub mod mmap;
+pub mod multiline;
+pub mod output;
+pub mod rank;
+pub mod repl;
+pub mod rust_lexer;
+pub mod server;
+pub mod stats;
+
+use cancel::{Cancel, Cancelled};
+use config_file::Settings;
+use delimited::Column;
+use diff::Side;
+use output::{Format, Found, ListFiles};
+use rust_lexer::Class;
+use stats::Stats;
+
+#[derive(Debug, Default, Clone)]
+pub struct Config {
+    pub key: String,
+    // 可以是文件，也可以是目录（递归搜索目录下的所有文件）
+    pub files: Vec<String>,
+    pub ignore_case: bool,
+    // 多行模式下 key 可以包含换行符（命令行中写作 `\n`），匹配可以跨越多行
+    pub multiline: bool,
+    // 设置了分隔符时按 CSV/TSV 解析输入，只在 columns 指定的列中查找（为空时查找所有列）
+    pub delimiter: Option<char>,
+    pub columns: Vec<Column>,
+    // 只输出匹配到的子串，每次匹配输出一行
+    pub only_matching: bool,
+    // 每个文件最多输出多少个匹配的行，达到上限后不再读取文件剩余的内容
+    pub max_count: Option<usize>,
+    // 输出匹配行前后各多少行的上下文
+    pub context: usize,
+    pub format: Format,
+    // 搜索结束后输出统计信息：文本格式输出到 stderr，JSON 格式作为最后一个对象输出到 stdout
+    pub stats: bool,
+    // 交互模式：文件只加载一次，然后逐行读取查询，此时所有的位置参数都是文件
+    pub interactive: bool,
+    // 排序模式：用 BM25 给文件（paragraphs 时为段落）打分，只输出得分最高的 rank 个
+    pub rank: Option<usize>,
+    pub paragraphs: bool,
+    // HTTP 服务模式监听的地址，同样所有的位置参数都是文件
+    pub serve: Option<String>,
+    // 不为空时把文件当作 Rust 源码切分成代码、注释和字符串，只保留完全落在这些类别中的匹配
+    pub syntax: Vec<Class>,
+    // 补丁模式：输入是 unified diff，只在新增和/或删除的行中查找；没有给出文件时读取标准输入
+    pub diff: Option<Side>,
+    // 不小于这个大小的普通文件映射到内存中搜索，None（默认）时总是读取文件。
+    // 映射期间文件被截断或改写是未定义行为，所以只在用 --mmap-threshold 明确要求时才使用
+    pub mmap_threshold: Option<u64>,
+    // -l/-L 时只输出文件的路径（忽略 --json），null 时每个路径以 NUL 结尾，
+    // path_separator 替换路径中的分隔符
+    pub list_files: Option<ListFiles>,
+    pub null: bool,
+    pub path_separator: Option<u8>,
+    // 搜索超过 timeout 后停止，输出已经找到的结果；也可以通过 cancel 的克隆在其他线程中取消搜索
+    pub timeout: Option<Duration>,
+    pub cancel: Cancel,
+}
+
+impl Config {
+    // 使用迭代器作为参数，没有在命令行中给出的选项使用配置文件中的值
+    pub fn build_config(args: impl Iterator<Item = String>) -> Result<Config, &'static str> {
+        Self::build_config_with(args, &Settings::discover()?)
+    }
+
+    /// 使用给定的配置文件内容解析命令行参数。
+    /// 同一个选项的优先级：命令行选项 > 环境变量 IGNORE_CASE > 预设 > 配置文件中的默认值
+    pub fn build_config_with(
+        args: impl Iterator<Item = String>,
+        settings: &Settings,
+    ) -> Result<Config, &'static str> {
+        Self::build(args, settings, env::var("IGNORE_CASE").ok())
+    }
+
+    // ignore_case_env 是环境变量 IGNORE_CASE 的值，作为参数传入，测试中不必修改进程的环境变量
+    fn build(
+        mut args: impl Iterator<Item = String>,
+        settings: &Settings,
+        ignore_case_env: Option<String>,
+    ) -> Result<Config, &'static str> {
+        args.next();
+
+        // 以 - 开头的参数是选项，可以出现在任意位置，其余的按顺序作为位置参数
+        // 配置文件中也可以设置的选项先用 None 表示命令行中没有给出
+        let mut multiline = false;
+        let mut delimiter = None;
+        let mut columns = Vec::new();
+        let mut ignore_case = None;
+        let mut only_matching = None;
+        let mut max_count = None;
+        let mut context = None;
+        let mut format = None;
+        let mut stats = None;
+        let mut interactive = false;
+        let mut serve = None;
+        let mut rank = None;
+        let mut paragraphs = false;
+        let mut syntax = None;
+        let mut diff = None;
+        let mut preset = None;
+        let mut mmap_threshold = None;
+        let mut list_files = None;
+        let mut null = false;
+        let mut path_separator = None;
+        let mut timeout = None;
+        let mut positional = Vec::new();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--multiline" => multiline = true,
+                "--csv" => delimiter = Some(','),
+                "--tsv" => delimiter = Some('\t'),
+                "--columns" => match args.next() {
+                    Some(list) => columns = delimited::parse_columns(&list)?,
+                    None => return Err("Didn't get a column list"),
+                },
+                "-i" | "--ignore-case" => ignore_case = Some(true),
+                "--case-sensitive" => ignore_case = Some(false),
+                "-o" | "--only-matching" => only_matching = Some(true),
+                "-m" | "--max-count" => match args.next().map(|n| n.parse()) {
+                    Some(Ok(n)) => max_count = Some(n),
+                    Some(Err(_)) => return Err("Invalid max count"),
+                    None => return Err("Didn't get a max count"),
+                },
+                "-C" | "--context" => match args.next().map(|n| n.parse()) {
+                    Some(Ok(n)) => context = Some(n),
+                    Some(Err(_)) => return Err("Invalid context"),
+                    None => return Err("Didn't get a context"),
+                },
+                "--json" => format = Some(Format::Json),
+                "--stats" => stats = Some(true),
+                "-I" | "--interactive" => interactive = true,
+                "--rank" => match args.next().map(|n| n.parse()) {
+                    Some(Ok(0)) | Some(Err(_)) => return Err("Invalid rank count"),
+                    Some(Ok(n)) => rank = Some(n),
+                    None => return Err("Didn't get a rank count"),
+                },
+                "--paragraphs" => paragraphs = true,
+                "--serve" => match args.next() {
+                    Some(addr) => serve = Some(addr),
+                    None => return Err("Didn't get an address to serve on"),
+                },
+                "--only" => match args.next() {
+                    Some(list) => syntax = Some(rust_lexer::parse_classes(&list)?),
+                    None => return Err("Didn't get a syntax class list"),
+                },
+                "--diff" => match args.next().map(|side| side.parse()) {
+                    Some(Ok(side)) => diff = Some(side),
+                    Some(Err(err)) => return Err(err),
+                    None => return Err("Didn't get a diff side"),
+                },
+                "--mmap-threshold" => match args.next().map(|n| n.parse()) {
+                    Some(Ok(n)) => mmap_threshold = Some(n),
+                    Some(Err(_)) => return Err("Invalid mmap threshold"),
+                    None => return Err("Didn't get an mmap threshold"),
+                },
+                "--no-mmap" => mmap_threshold = None,
+                "-l" | "--files-with-matches" => list_files = Some(ListFiles::WithMatches),
+                "-L" | "--files-without-match" => list_files = Some(ListFiles::WithoutMatch),
+                "--null" => null = true,
+                "--path-separator" => match args.next().map(|sep| sep.into_bytes()) {
+                    Some(sep) if sep.len() == 1 => path_separator = Some(sep[0]),
+                    Some(_) => return Err("Path separator must be a single byte"),
+                    None => return Err("Didn't get a path separator"),
+                },
+                "--timeout" => match args.next() {
+                    Some(t) => timeout = Some(cancel::parse_timeout(&t)?),
+                    None => return Err("Didn't get a timeout"),
+                },
+                "-p" | "--preset" => match args.next() {
+                    Some(name) => preset = Some(name),
+                    None => return Err("Didn't get a preset name"),
+                },
+                _ => positional.push(arg),
+            }
+        }
+
+        // 排序和 diff 模式有自己的输出，不支持逐行搜索的这些选项，在命令行中给出时报错而不是悄悄忽略；
+        // 配置文件中的默认值对所有搜索生效，不在这里检查
+        let line_options = syntax.is_some()
+            || delimiter.is_some()
+            || context.is_some()
+            || only_matching.is_some()
+            || multiline;
+        if rank.is_some() && line_options {
+            return Err("--rank can't be used with --only, --csv, --tsv, -C, -o or --multiline");
+        }
+        if diff.is_some() && line_options {
+            return Err("--diff can't be used with --only, --csv, --tsv, -C, -o or --multiline");
+        }
+
+        let file = settings.resolve(preset.as_deref())?;
+        let only_matching = only_matching.or(file.only_matching).unwrap_or(false);
+        let max_count = max_count.or(file.max_count);
+        let context = context.or(file.context).unwrap_or(0);
+        let format = format.or(file.format).unwrap_or_default();
+        let stats = stats.or(file.stats).unwrap_or(false);
+        let syntax = syntax.or(file.syntax).unwrap_or_default();
+
+        if !columns.is_empty() && delimiter.is_none() {
+            return Err("--columns requires --csv or --tsv");
+        }
+        if !syntax.is_empty() && (delimiter.is_some() || multiline) {
+            return Err("--only can't be used with --csv, --tsv or --multiline");
+        }
+        // -l/-L 只输出文件名，排序和 diff 模式的输出不是按文件组织的
+        if list_files.is_some() && (rank.is_some() || diff.is_some()) {
+            return Err("-l and -L can't be used with --rank or --diff");
+        }
+        // 服务模式只支持普通的逐行搜索
+        if serve.is_some() && (rank.is_some() || diff.is_some() || list_files.is_some()) {
+            return Err("--serve can't be used with --rank, --diff, -l or -L");
+        }
+        // 交互模式在加载的文件中查询，不读取补丁；查询之间没有超时，也不会作为服务运行
+        if interactive && (diff.is_some() || serve.is_some() || timeout.is_some()) {
+            return Err("-I can't be used with --diff, --serve or --timeout");
+        }
+
+        // 兼容原来的用法：最后一个位置参数为 ignore_case 时忽略大小写
+        // 交互模式和服务模式下的 key 在每次查询时输入，使用预设时 key 来自预设
+        let without_key = interactive || serve.is_some();
+        let key_from_preset = !without_key && file.pattern.is_some();
+        // 补丁模式下可以不给出文件，从标准输入读取
+        let min_len = if without_key || key_from_preset || diff.is_some() {
+            1
+        } else {
+            2
+        };
+        let ignore_case_arg =
+            positional.len() > min_len && positional.last().unwrap() == "ignore_case";
+        if ignore_case_arg {
+            positional.pop();
+        }
+        let mut positional = positional.into_iter();
+
+        let key = if without_key {
+            String::new()
+        } else {
+            match file.pattern.or_else(|| positional.next()) {
+                Some(arg) if multiline => multiline::unescape(&arg),
+                Some(arg) => arg,
+                None => return Err("Didn't get a query string"),
+            }
+        };
+        let files: Vec<String> = positional.collect();
+        if files.is_empty() && diff.is_none() {
+            return Err("Didn't get a file path");
+        }
+
+        // 命令行中的 -i/--case-sensitive 优先；其次是环境变量，IGNORE_CASE=1 时忽略大小写；
+        // 该环境变量不存在时，检查命令行参数，最后才是配置文件
+        let ignore_case = match (ignore_case, ignore_case_env) {
+            (Some(ignore_case), _) => ignore_case,
+            (None, Some(env)) => env.eq("1"),
+            (None, None) => ignore_case_arg || file.ignore_case.unwrap_or(false),
+        };
+
+        Ok(Config {
+            key,
+            files,
+            ignore_case,
+            multiline,
+            delimiter,
+            columns,
+            only_matching,
+            max_count,
+            context,
+            format,
+            stats,
+            interactive,
+            rank,
+            paragraphs,
+            serve,
+            syntax,
+            diff,
+            mmap_threshold,
+            list_files,
+            null,
+            path_separator,
+            timeout,
+            cancel: Cancel::new(),
+        })
+    }
+}
+
+pub fn run(config: Config) -> Result<(), Box<dyn Error>> {
+    if config.interactive {
+        return repl::run(config);
+    }
+    if config.serve.is_some() {
+        return tokio::runtime::Runtime::new()?.block_on(server::run(config));
+    }
+
+    let start = Instant::now();
+    let config = match config.timeout {
+        Some(timeout) => Config {
+            cancel: config.cancel.with_deadline(start + timeout),
+            ..config
+        },
+        None => config,
+    };
+    let mut stats = Stats::default();
+    match (config.rank, config.diff) {
+        (Some(top_n), _) => rank::run(&config, top_n, &mut stats)?,
+        (None, Some(_)) => diff::run(&config, &mut stats)?,
+        (None, None) => search_files(&config, &mut stats)?,
+    }
+
+    stats.elapsed = start.elapsed();
+    if config.stats {
+        match config.format {
+            Format::Text => eprintln!("{}", stats),
+            Format::Json => println!("{}", stats.to_json()),
+        }
+    }
+
+    // 已经输出了部分结果，通过错误告诉调用方搜索没有完成
+    if stats.cancelled {
+        return Err(Box::new(Cancelled));
+    }
+    if stats.files_searched == 0 {
+        return Err("No file was searched".into());
+    }
+    Ok(())
+}
+
+// 检查搜索是否已经被取消或者超时，并记录到 stats 中
+fn cancelled(config: &Config, stats: &mut Stats) -> bool {
+    if config.cancel.is_cancelled() {
+        stats.cancelled = true;
+    }
+    stats.cancelled
+}
+
+// 依次搜索每个文件并输出结果
+fn search_files(config: &Config, stats: &mut Stats) -> io::Result<()> {
+    let Ok(files) = collect_files(&config.files, &config.cancel, stats) else {
+        return Ok(());
+    };
+    if let Some(list) = config.list_files {
+        return list_files(config, list, &files, stats, &mut io::stdout().lock());
+    }
+
+    let show_path = files.len() > 1;
+    for path in &files {
+        if cancelled(config, stats) {
+            break;
+        }
+        let found = match search_path(config, path, stats) {
+            Ok(found) => found,
+            Err(err) => {
+                stats.files_skipped += 1;
+                eprintln!("Skipped [{}]: {}", path.display(), err);
+                continue;
+            }
+        };
+        stats.files_searched += 1;
+
+        // 搜索多个文件时只输出有结果的文件
+        if show_path && found.is_empty() {
+            continue;
+        }
+        match config.format {
+            Format::Text => println!("{}", output::to_text(&found, show_path.then_some(path))),
+            Format::Json => println!("{}", output::to_json(&found, path)),
+        }
+    }
+    Ok(())
+}
+
+// -l/-L：只需要知道文件中有没有匹配，找到第一个匹配就可以停止读取
+fn list_files<W: Write>(
+    config: &Config,
+    list: ListFiles,
+    files: &[PathBuf],
+    stats: &mut Stats,
+    out: &mut W,
+) -> io::Result<()> {
+    let config = Config {
+        max_count: Some(1),
+        ..config.clone()
+    };
+    for path in files {
+        if cancelled(&config, stats) {
+            break;
+        }
+        let found = match search_path(&config, path, stats) {
+            Ok(found) => found,
+            Err(err) => {
+                stats.files_skipped += 1;
+                eprintln!("Skipped [{}]: {}", path.display(), err);
+                continue;
+            }
+        };
+        stats.files_searched += 1;
+
+        if found.is_empty() == (list == ListFiles::WithoutMatch) {
+            output::write_path(out, path, config.path_separator, config.null)?;
+        }
+    }
+    out.flush()
+}
+
+/// 展开参数中的目录，按路径排序递归收集其中的文件（跳过以 . 开头的隐藏文件和目录）。
+/// 遍历很大的目录树也需要时间，每处理一个目录项检查一次 cancel，取消时记录到 stats 中并返回 Cancelled
+pub fn collect_files(
+    paths: &[String],
+    cancel: &Cancel,
+    stats: &mut Stats,
+) -> Result<Vec<PathBuf>, Cancelled> {
+    let mut files = Vec::new();
+    for path in paths {
+        check_cancel(cancel, stats)?;
+        let path = PathBuf::from(path);
+        if path.is_dir() {
+            walk_dir(&path, &mut files, cancel, stats)?;
+        } else {
+            // 不存在的文件也先收集起来，打开失败时再计入 files_skipped
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
+
+fn check_cancel(cancel: &Cancel, stats: &mut Stats) -> Result<(), Cancelled> {
+    if cancel.is_cancelled() {
+        stats.cancelled = true;
+        return Err(Cancelled);
+    }
+    Ok(())
+}
+
+fn walk_dir(
+    dir: &Path,
+    files: &mut Vec<PathBuf>,
+    cancel: &Cancel,
+    stats: &mut Stats,
+) -> Result<(), Cancelled> {
+    let mut entries: Vec<PathBuf> = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .collect(),
+        Err(err) => {
+            stats.files_skipped += 1;
+            eprintln!("Skipped [{}]: {}", dir.display(), err);
+            return Ok(());
+        }
+    };
+    entries.sort();
+
+    for path in entries {
+        check_cancel(cancel, stats)?;
+        let hidden = path
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with('.'));
+        if hidden {
+            continue;
+        }
+        if path.is_dir() {
+            walk_dir(&path, files, cancel, stats)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// 按 config 中的搜索模式搜索单个文件
+pub fn search_path(config: &Config, path: &Path, stats: &mut Stats) -> io::Result<Found> {
+    let mut file = File::open(path)?;
+    // 大文件映射到内存后与读入内存的内容走同样的匹配逻辑
+    if let Some(map) = config
+        .mmap_threshold
+        .and_then(|threshold| mmap::map(&file, threshold))
+    {
+        return search_contents(config, mmap::as_str(&map)?, stats);
+    }
+
+    // 这几种模式都需要整个文件的内容：词法分析时块注释和字符串可能跨越多行，
+    // 快速路径在整个缓冲区中查找；设置了 max_count 时，或者输入是管道等可能没有尽头的特殊文件时，
+    // 仍然逐行读取，以便提前结束
+    let fast_path = fast::applies(config)
+        && config.max_count.is_none()
+        && file.metadata().is_ok_and(|metadata| metadata.is_file());
+    if config.delimiter.is_some() || config.multiline || !config.syntax.is_empty() || fast_path {
+        let mut contents = String::new();
+        file.read_to_string(&mut contents)?;
+        return search_contents(config, &contents, stats);
+    }
+
+    // 逐行读取而不是一次读入整个文件，这样达到 max_count 以后就可以提前结束
+    search_reader(config, BufReader::new(file), stats).map(Found::Lines)
+}
+
+/// 按 config 中的搜索模式搜索已经读入内存的内容
+pub fn search_contents(config: &Config, contents: &str, stats: &mut Stats) -> io::Result<Found> {
+    let max_count = config.max_count.unwrap_or(usize::MAX);
+
+    if let Some(delimiter) = config.delimiter {
+        stats.bytes_scanned += contents.len();
+        stats.lines_scanned += contents.lines().count();
+        let records = delimited::search_columns(
+            &config.key,
+            contents,
+            delimiter,
+            &config.columns,
+            config.ignore_case,
+            max_count,
+        )
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))?;
+        // 第一条是表头，不计入匹配数
+        stats.matched_lines += records.len().saturating_sub(1);
+        stats.matches += records.len().saturating_sub(1);
+        return Ok(Found::Records(records, delimiter));
+    }
+
+    if config.multiline {
+        stats.bytes_scanned += contents.len();
+        stats.lines_scanned += contents.lines().count();
+        let spans: Vec<_> = multiline::search_multiline(&config.key, contents, config.ignore_case)
+            .into_iter()
+            .take(max_count)
+            .map(|span| (span.start_line, span.end_line, span.text.to_string()))
+            .collect();
+        stats.matched_lines += spans
+            .iter()
+            .map(|(start, end, _)| end - start + 1)
+            .sum::<usize>();
+        stats.matches += spans.len();
+        return Ok(Found::Spans(spans));
+    }
+
+    if !config.syntax.is_empty() {
+        let spans = rust_lexer::classify(contents);
+        let allow = |start, end| rust_lexer::is_within(&spans, start, end, &config.syntax);
+        return search_lines(config, contents.as_bytes(), stats, allow).map(Found::Lines);
+    }
+
+    if fast::applies(config) {
+        return Ok(Found::Lines(fast::search_lines(config, contents, stats)));
+    }
+
+    search_reader(config, contents.as_bytes(), stats).map(Found::Lines)
+}
+
+/// 逐行读取 reader 并查找 config.key。
+/// 开启 only_matching 时每次匹配返回一个子串，否则返回整行以及前后 context 行的上下文，
+/// 不相邻的两组结果之间用 `--` 分隔；匹配的行数达到 max_count 后立即停止读取。
+pub fn search_reader<R: BufRead>(
+    config: &Config,
+    reader: R,
+    stats: &mut Stats,
+) -> io::Result<Vec<String>> {
+    search_lines(config, reader, stats, |_, _| true)
+}
+
+// allow 根据匹配在整个输入中的字节区间 [start, end) 判断这次匹配是否有效
+fn search_lines<R: BufRead>(
+    config: &Config,
+    mut reader: R,
+    stats: &mut Stats,
+    allow: impl Fn(usize, usize) -> bool,
+) -> io::Result<Vec<String>> {
+    let mut results = Vec::new();
+    let max_count = config.max_count.unwrap_or(usize::MAX);
+    if max_count == 0 {
+        return Ok(results);
+    }
+    let context = if config.only_matching {
+        0
+    } else {
+        config.context
+    };
+
+    // 还没有输出的前文，最多保留 context 行
+    let mut before: VecDeque<String> = VecDeque::with_capacity(context);
+    // 还需要输出多少行后文
+    let mut after = 0;
+    // 最后一个输出的行号，用来判断是否需要输出分隔符
+    let mut last_output = None;
+    let mut line_number = 0;
+    let mut matched_lines = 0;
+    // 当前行在输入中的起始位置
+    let mut offset = 0;
+    let mut buf = String::new();
+    loop {
+        // 从第 0 行开始每隔 CHECK_INTERVAL 行检查一次，取消时返回已经找到的结果
+        if line_number % cancel::CHECK_INTERVAL == 0 && cancelled(config, stats) {
+            break;
+        }
+        buf.clear();
+        let bytes = reader.read_line(&mut buf)?;
+        if bytes == 0 {
+            break;
+        }
+        stats.bytes_scanned += bytes;
+        stats.lines_scanned += 1;
+        line_number += 1;
+        let line_start = offset;
+        offset += bytes;
+
+        let line = buf.strip_suffix('\n').unwrap_or(&buf);
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        let mut matches = matcher::find_iter(&config.key, line, config.ignore_case)
+            .filter(|&(start, end)| allow(line_start + start, line_start + end))
+            .peekable();
+        // 空的 key 匹配所有的行
+        if !config.key.is_empty() && matches.peek().is_none() {
+            if after > 0 {
+                after -= 1;
+                results.push(line.to_string());
+                last_output = Some(line_number);
+            } else if context > 0 {
+                if before.len() == context {
+                    before.pop_front();
+                }
+                before.push_back(line.to_string());
+            }
+            continue;
+        }
+
+        if context > 0 {
+            let first = line_number - before.len();
+            if last_output.is_some_and(|last| first > last + 1) {
+                results.push("--".to_string());
+            }
+            results.extend(before.drain(..));
+        }
+
+        if config.only_matching {
+            let before = results.len();
+            results.extend(matches.map(|(start, end)| line[start..end].to_string()));
+            stats.matches += results.len() - before;
+        } else {
+            stats.matches += matches.count().max(1);
+            results.push(line.to_string());
+        }
+        last_output = Some(line_number);
+        after = context;
+
+        stats.matched_lines += 1;
+        matched_lines += 1;
+        if matched_lines == max_count {
+            break;
+        }
+    }
+    Ok(results)
+}
+
+pub fn search<'a>(key: &str, contents: &'a str) -> Vec<&'a str> {
+    contents.lines().filter(|line| line.contains(key)).collect()
+}
+
+pub fn search_case_insensitive<'a>(key: &str, contents: &'a str) -> Vec<&'a str> {
+    contents
+        .lines()
+        .filter(|line| line.to_lowercase().contains(&key.to_lowercase()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn case_sensitive() {
+        let query = "duct";
+        let contents = "\
+Rust:
+safe, fast, productive.
+Pick three.
+Duct tape.";
+
+        assert_eq!(vec!["safe, fast, productive."], search(query, contents));
+    }
+
+    #[test]
+    fn case_insensitive() {
+        let query = "rUsT";
+        let contents = "\
+Rust:
+safe, fast, productive.
+Pick three.
+Trust me.";
+
+        assert_eq!(
+            vec!["Rust:", "Trust me."],
+            search_case_insensitive(query, contents)
+        );
+    }
+
+    // 不读取配置文件和环境变量 IGNORE_CASE，测试结果不受运行测试的机器上的 ~/.minigrep.toml
+    // 和 shell 中导出的环境变量影响
+    fn build(args: &[&str]) -> Result<Config, &'static str> {
+        Config::build(
+            args.iter().map(|arg| arg.to_string()),
+            &Settings::default(),
+            None,
+        )
+    }
+
+    #[test]
+    fn build_multiline_config() {
+        let args = ["minigrep", "--multiline", r"panicked\nnote:", "poem.txt"];
+        let config = build(&args).unwrap();
+        assert!(config.multiline);
+        assert_eq!(config.key, "panicked\nnote:");
+        assert_eq!(config.files, vec!["poem.txt"]);
+
+        let args = ["minigrep", r"a\nb", "poem.txt"];
+        let config = build(&args).unwrap();
+        assert!(!config.multiline);
+        assert_eq!(config.key, r"a\nb");
+    }
+
+    #[test]
+    fn build_csv_config() {
+        let args = [
+            "minigrep",
+            "--csv",
+            "lisi",
+            "data.csv",
+            "--columns",
+            "name,2",
+        ];
+        let config = build(&args).unwrap();
+        assert_eq!(config.delimiter, Some(','));
+        assert_eq!(
+            config.columns,
+            vec![Column::Name("name".to_string()), Column::Index(2)]
+        );
+        assert_eq!(config.files, vec!["data.csv"]);
+
+        let args = ["minigrep", "lisi", "data.csv", "--columns", "name"];
+        assert!(build(&args).is_err());
+    }
+
+    #[test]
+    fn only_matching() {
+        let config = Config {
+            key: "id=".to_string(),
+            only_matching: true,
+            ..Default::default()
+        };
+        let contents = "\
+user id=1 id=2
+nothing here
+ID=3";
+        assert_eq!(
+            search_reader(&config, contents.as_bytes(), &mut Stats::default()).unwrap(),
+            vec!["id=", "id="]
+        );
+
+        let config = Config {
+            key: "id=".to_string(),
+            ignore_case: true,
+            only_matching: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            search_reader(&config, contents.as_bytes(), &mut Stats::default()).unwrap(),
+            vec!["id=", "id=", "ID="]
+        );
+    }
+
+    #[test]
+    fn max_count() {
+        let contents = "rust 1\nrust 2\ngo\nrust 3";
+        let config = Config {
+            key: "rust".to_string(),
+            max_count: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(
+            search_reader(&config, contents.as_bytes(), &mut Stats::default()).unwrap(),
+            vec!["rust 1", "rust 2"]
+        );
+
+        // max_count 限制的是行数，同一行中的多次匹配都会输出
+        let config = Config {
+            key: "s".to_string(),
+            only_matching: true,
+            max_count: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(
+            search_reader(&config, "ss\ns".as_bytes(), &mut Stats::default()).unwrap(),
+            vec!["s", "s"]
+        );
+
+        let args = ["minigrep", "-m", "x", "rust", "poem.txt"];
+        assert!(build(&args).is_err());
+    }
+
+    #[test]
+    fn stop_reading_at_max_count() {
+        // 第二行不是合法的 UTF-8，如果被读取就会返回错误
+        let contents: &[u8] = b"rust 1\n\xff\xfe\n";
+        let config = Config {
+            key: "rust".to_string(),
+            max_count: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(
+            search_reader(&config, contents, &mut Stats::default()).unwrap(),
+            vec!["rust 1"]
+        );
+
+        let config = Config {
+            key: "rust".to_string(),
+            ..Default::default()
+        };
+        assert!(search_reader(&config, contents, &mut Stats::default()).is_err());
+    }
+
+    #[test]
+    fn build_multi_file_config() {
+        let args = ["minigrep", "--json", "rust", "a.txt", "src", "--stats"];
+        let config = build(&args).unwrap();
+        assert_eq!(config.files, vec!["a.txt", "src"]);
+        assert_eq!(config.format, Format::Json);
+        assert!(config.stats);
+
+        // 兼容原来的用法，最后的 ignore_case 不会被当作文件
+        let args = ["minigrep", "rust", "a.txt", "ignore_case"];
+        let config = build(&args).unwrap();
+        assert_eq!(config.files, vec!["a.txt"]);
+    }
+
+    #[test]
+    fn context_lines() {
+        let contents = "1\nrust 2\n3\n4\n5\n6\nrust 7\nrust 8\n9";
+        let config = Config {
+            key: "rust".to_string(),
+            context: 1,
+            ..Default::default()
+        };
+        assert_eq!(
+            search_reader(&config, contents.as_bytes(), &mut Stats::default()).unwrap(),
+            vec!["1", "rust 2", "3", "--", "6", "rust 7", "rust 8", "9"]
+        );
+
+        // 上下文重叠或相邻时不输出分隔符
+        let config = Config {
+            context: 2,
+            ..config
+        };
+        assert_eq!(
+            search_reader(&config, contents.as_bytes(), &mut Stats::default()).unwrap(),
+            vec!["1", "rust 2", "3", "4", "5", "6", "rust 7", "rust 8", "9"]
+        );
+
+        let args = ["minigrep", "-C", "2", "-I", "a.txt", "b.txt"];
+        let config = build(&args).unwrap();
+        assert_eq!(config.context, 2);
+        assert!(config.interactive);
+        assert_eq!(config.key, "");
+        assert_eq!(config.files, vec!["a.txt", "b.txt"]);
+
+        // 交互模式不支持的选项直接报错，而不是被悄悄忽略
+        for option in [
+            &["--diff", "added"][..],
+            &["--serve", "127.0.0.1:8080"],
+            &["--timeout", "1s"],
+        ] {
+            let mut args = vec!["minigrep", "-I", "a.txt"];
+            args.extend_from_slice(option);
+            assert!(build(&args).is_err(), "{:?}", option);
+        }
+    }
+
+    #[test]
+    fn search_stats() {
+        let config = Config {
+            key: "rust".to_string(),
+            ..Default::default()
+        };
+        let mut stats = Stats::default();
+        let contents = "rust rust\r\ngo\nrust\n";
+        let results = search_reader(&config, contents.as_bytes(), &mut stats).unwrap();
+        assert_eq!(results, vec!["rust rust", "rust"]);
+        assert_eq!(stats.bytes_scanned, contents.len());
+        assert_eq!(stats.lines_scanned, 3);
+        assert_eq!(stats.matched_lines, 2);
+        assert_eq!(stats.matches, 3);
+    }
+
+    #[test]
+    fn search_directory() {
+        let mut stats = Stats::default();
+        let files =
+            collect_files(&["src/examples".to_string()], &Cancel::new(), &mut stats).unwrap();
+        assert_eq!(
+            files,
+            vec![
+                PathBuf::from("src/examples/minigrep.rs"),
+                PathBuf::from("src/examples/test.txt"),
+            ]
+        );
+
+        let config = Config {
+            key: "nobody".to_string(),
+            ..Default::default()
+        };
+        let found = search_path(&config, &files[1], &mut stats).unwrap();
+        assert_eq!(
+            found,
+            Found::Lines(vec![
+                "I'm nobody! Who are you?".to_string(),
+                "Are you nobody, too?".to_string(),
+            ])
+        );
+        assert_eq!(stats.lines_scanned, 16);
+        assert!(search_path(&config, Path::new("src/examples/none.txt"), &mut stats).is_err());
+
+        // 映射到内存和读取文件得到同样的结果
+        let mapped = Config {
+            mmap_threshold: Some(0),
+            ..config
+        };
+        assert_eq!(search_path(&mapped, &files[1], &mut stats).unwrap(), found);
+
+        // 默认不映射，只有明确给出阈值时才使用
+        let args = ["minigrep", "rust", "a.txt"];
+        assert_eq!(build(&args).unwrap().mmap_threshold, None);
+        let args = ["minigrep", "rust", "a.txt", "--mmap-threshold", "1024"];
+        let config = build(&args).unwrap();
+        assert_eq!(config.mmap_threshold, Some(1024));
+        let args = [
+            "minigrep",
+            "rust",
+            "a.txt",
+            "--mmap-threshold",
+            "1024",
+            "--no-mmap",
+        ];
+        let config = build(&args).unwrap();
+        assert_eq!(config.mmap_threshold, None);
+    }
+
+    #[test]
+    fn syntax_filter() {
+        let contents = r#"// 读取 config
+let config = "config.toml";
+/* 多行注释中的
+   config */
+let path = config;"#;
+        let config = Config {
+            key: "config".to_string(),
+            syntax: vec![Class::Comment],
+            ..Default::default()
+        };
+        let found = search_contents(&config, contents, &mut Stats::default()).unwrap();
+        assert_eq!(
+            found,
+            Found::Lines(vec![
+                "// 读取 config".to_string(),
+                "   config */".to_string()
+            ])
+        );
+
+        // 同一行中只有落在允许类别中的匹配才会被计数和输出
+        let config = Config {
+            syntax: vec![Class::Code],
+            only_matching: true,
+            ..config
+        };
+        let mut stats = Stats::default();
+        let found = search_contents(&config, contents, &mut stats).unwrap();
+        assert_eq!(found, Found::Lines(vec!["config".to_string(); 2]));
+        assert_eq!((stats.matched_lines, stats.matches), (2, 2));
+
+        let args = ["minigrep", "--only", "comment,string", "config", "a.rs"];
+        let config = build(&args).unwrap();
+        assert_eq!(config.syntax, vec![Class::Comment, Class::String]);
+
+        let args = ["minigrep", "--only", "doc", "config", "a.rs"];
+        assert!(build(&args).is_err());
+        let args = [
+            "minigrep",
+            "--multiline",
+            "--only",
+            "code",
+            "config",
+            "a.rs",
+        ];
+        assert!(build(&args).is_err());
+    }
+
+    #[test]
+    fn build_rank_config() {
+        let args = [
+            "minigrep",
+            "--rank",
+            "3",
+            "--paragraphs",
+            "rust safe",
+            "src",
+        ];
+        let config = build(&args).unwrap();
+        assert_eq!(config.rank, Some(3));
+        assert!(config.paragraphs);
+
+        for count in ["0", "-1", "x"] {
+            let args = ["minigrep", "--rank", count, "rust", "src"];
+            assert_eq!(build(&args).unwrap_err(), "Invalid rank count");
+        }
+
+        // 排序模式不支持的选项直接报错，而不是被悄悄忽略
+        for option in [
+            &["--only", "code"][..],
+            &["--csv"],
+            &["--tsv"],
+            &["-C", "1"],
+            &["-o"],
+            &["--multiline"],
+        ] {
+            let mut args = vec!["minigrep", "--rank", "3", "rust", "src"];
+            args.extend_from_slice(option);
+            assert!(build(&args).is_err(), "{:?}", option);
+        }
+    }
+
+    #[test]
+    fn build_diff_config() {
+        let args = ["minigrep", "--diff", "added", "unwrap()"];
+        let config = build(&args).unwrap();
+        assert_eq!(config.diff, Some(Side::Added));
+        assert_eq!(config.key, "unwrap()");
+        assert!(config.files.is_empty());
+
+        let args = ["minigrep", "--diff", "both", "unwrap()", "a.patch"];
+        let config = build(&args).unwrap();
+        assert_eq!(config.diff, Some(Side::Both));
+        assert_eq!(config.files, vec!["a.patch"]);
+
+        let args = ["minigrep", "--diff", "new", "unwrap()"];
+        assert!(build(&args).is_err());
+        let args = ["minigrep", "unwrap()"];
+        assert!(build(&args).is_err());
+
+        // search_diff 只使用 key、ignore_case、max_count 和 diff，其他搜索模式的选项直接报错
+        for option in [
+            &["--only", "code"][..],
+            &["--csv"],
+            &["--tsv"],
+            &["-C", "1"],
+            &["-o"],
+            &["--multiline"],
+        ] {
+            let mut args = vec!["minigrep", "--diff", "added", "unwrap()"];
+            args.extend_from_slice(option);
+            assert!(build(&args).is_err(), "{:?}", option);
+        }
+        let args = ["minigrep", "--diff", "added", "-i", "-m", "2", "unwrap()"];
+        assert!(build(&args).is_ok());
+    }
+
+    #[test]
+    fn build_serve_config() {
+        let args = ["minigrep", "--serve", "127.0.0.1:8080", "src"];
+        let config = build(&args).unwrap();
+        assert_eq!(config.serve.as_deref(), Some("127.0.0.1:8080"));
+        assert_eq!(config.files, vec!["src"]);
+
+        // 服务模式下不支持的选项直接报错，而不是被悄悄忽略
+        for option in [&["--rank", "3"][..], &["--diff", "added"], &["-l"], &["-L"]] {
+            let mut args = vec!["minigrep", "--serve", "127.0.0.1:8080", "src"];
+            args.extend_from_slice(option);
+            assert!(build(&args).is_err(), "{:?}", option);
+        }
+    }
+
+    #[test]
+    fn build_config_with_file() {
+        let settings = Settings::parse(
+            r#"
+context = 1
+stats = true
+ignore_case = false
+
+[presets]
+errors = { pattern = "error", context = 2, format = "json" }
+"#,
+        )
+        .unwrap();
+        let build = |args: &[&str]| {
+            Config::build_config_with(args.iter().map(|arg| arg.to_string()), &settings)
+        };
+
+        // 没有在命令行中给出的选项使用文件中的默认值
+        let config = build(&["minigrep", "rust", "a.rs"]).unwrap();
+        assert_eq!((config.context, config.stats), (1, true));
+        assert_eq!(config.format, Format::Text);
+
+        // 预设覆盖默认值，命令行覆盖预设；预设给出了 key，位置参数都是文件
+        let config = build(&["minigrep", "--preset", "errors", "a.rs", "b.rs", "-C", "3"]).unwrap();
+        assert_eq!(config.key, "error");
+        assert_eq!(config.files, vec!["a.rs", "b.rs"]);
+        assert_eq!((config.context, config.format), (3, Format::Json));
+        assert!(build(&["minigrep", "--preset", "none", "a.rs"]).is_err());
+    }
+
+    // 同一个选项的每一级优先级分别测试：命令行选项 > 环境变量 IGNORE_CASE > 预设 > 文件中的默认值
+    const PRECEDENCE: &str = r#"
+ignore_case = true
+context = 1
+
+[presets]
+strict = { pattern = "error", ignore_case = false, context = 2 }
+"#;
+
+    fn build_with_env(args: &[&str], env: Option<&str>) -> Config {
+        let settings = Settings::parse(PRECEDENCE).unwrap();
+        let args = args.iter().map(|arg| arg.to_string());
+        Config::build(args, &settings, env.map(String::from)).unwrap()
+    }
+
+    #[test]
+    fn precedence_file_defaults() {
+        let config = build_with_env(&["minigrep", "rust", "a.rs"], None);
+        assert!(config.ignore_case);
+        assert_eq!(config.context, 1);
+    }
+
+    #[test]
+    fn precedence_preset_over_file() {
+        let config = build_with_env(&["minigrep", "-p", "strict", "a.rs"], None);
+        assert!(!config.ignore_case);
+        assert_eq!(config.context, 2);
+    }
+
+    #[test]
+    fn precedence_env_over_preset() {
+        let config = build_with_env(&["minigrep", "-p", "strict", "a.rs"], Some("1"));
+        assert!(config.ignore_case);
+        // 环境变量存在但不为 1 时也会覆盖预设和文件，结果是区分大小写
+        let config = build_with_env(&["minigrep", "rust", "a.rs"], Some("0"));
+        assert!(!config.ignore_case);
+    }
+
+    #[test]
+    fn precedence_cli_over_env() {
+        let args = [
+            "minigrep",
+            "--case-sensitive",
+            "-p",
+            "strict",
+            "a.rs",
+            "-C",
+            "3",
+        ];
+        let config = build_with_env(&args, Some("1"));
+        assert!(!config.ignore_case);
+        assert_eq!(config.context, 3);
+        let config = build_with_env(&["minigrep", "-i", "-p", "strict", "a.rs"], Some("0"));
+        assert!(config.ignore_case);
+    }
+
+    #[test]
+    fn list_matching_files() {
+        let config = Config {
+            key: "nobody".to_string(),
+            null: true,
+            ..Default::default()
+        };
+        let files = collect_files(
+            &["src/examples".to_string()],
+            &Cancel::new(),
+            &mut Stats::default(),
+        )
+        .unwrap();
+        let list = |list| {
+            let mut out = Vec::new();
+            list_files(&config, list, &files, &mut Stats::default(), &mut out).unwrap();
+            out
+        };
+        assert_eq!(list(ListFiles::WithMatches), b"src/examples/test.txt\0");
+        assert_eq!(list(ListFiles::WithoutMatch), b"src/examples/minigrep.rs\0");
+
+        let args = [
+            "minigrep",
+            "-l",
+            "--null",
+            "--path-separator",
+            "\\",
+            "x",
+            "src",
+        ];
+        let config = build(&args).unwrap();
+        assert_eq!(config.list_files, Some(ListFiles::WithMatches));
+        assert!(config.null);
+        assert_eq!(config.path_separator, Some(b'\\'));
+
+        let args = ["minigrep", "--path-separator", "::", "x", "src"];
+        assert!(build(&args).is_err());
+
+        // 与排序和 diff 模式一起使用时报错，而不是被悄悄忽略
+        for list in ["-l", "-L"] {
+            for option in [&["--rank", "3"][..], &["--diff", "added"]] {
+                let mut args = vec!["minigrep", list];
+                args.extend_from_slice(option);
+                args.push("x");
+                assert!(build(&args).is_err(), "{} {:?}", list, option);
+            }
+        }
+    }
+
+    // 无穷无尽的输入，每 1000 行中有一行 rust
+    struct Endless {
+        pattern: Vec<u8>,
+        pos: usize,
+    }
+
+    impl Read for Endless {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let n = buf.len().min(self.pattern.len() - self.pos);
+            buf[..n].copy_from_slice(&self.pattern[self.pos..self.pos + n]);
+            self.pos = (self.pos + n) % self.pattern.len();
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn cancel_search() {
+        let endless = || {
+            BufReader::new(Endless {
+                pattern: format!("rust\n{}", "go\n".repeat(999)).into_bytes(),
+                pos: 0,
+            })
+        };
+
+        // 超时后返回已经找到的部分结果
+        let config = Config {
+            key: "rust".to_string(),
+            cancel: Cancel::new().with_deadline(Instant::now() + Duration::from_millis(50)),
+            ..Default::default()
+        };
+        let mut stats = Stats::default();
+        let results = search_reader(&config, endless(), &mut stats).unwrap();
+        assert!(stats.cancelled);
+        assert!(!results.is_empty());
+        assert_eq!(results.len(), stats.matched_lines);
+
+        // 在其他线程中取消
+        let config = Config {
+            key: "rust".to_string(),
+            ..Default::default()
+        };
+        let handle = config.cancel.clone();
+        let canceller = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(20));
+            handle.cancel();
+        });
+        let mut stats = Stats::default();
+        search_reader(&config, endless(), &mut stats).unwrap();
+        canceller.join().unwrap();
+        assert!(stats.cancelled);
+
+        // 已经取消时不再搜索任何文件，快速路径也一样
+        let config = Config {
+            files: vec!["src/examples".to_string()],
+            ..config
+        };
+        let mut stats = Stats::default();
+        search_files(&config, &mut stats).unwrap();
+        assert_eq!((stats.files_searched, stats.cancelled), (0, true));
+        let mut stats = Stats::default();
+        let found = search_contents(&config, "rust\nrust\n", &mut stats).unwrap();
+        assert!(found.is_empty());
+        assert!(stats.cancelled);
+
+        // 遍历目录时同样会检查是否已经取消，不会先把整个目录树遍历完
+        let root = env::temp_dir().join(format!("minigrep-walk-{}", std::process::id()));
+        for i in 0..20 {
+            let dir = root.join(format!("dir{:02}/nested", i));
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(dir.join("a.txt"), "rust").unwrap();
+        }
+        let paths = [root.to_string_lossy().into_owned()];
+        let mut stats = Stats::default();
+        assert_eq!(
+            collect_files(&paths, &Cancel::new(), &mut stats)
+                .unwrap()
+                .len(),
+            20
+        );
+        assert!(!stats.cancelled);
+        let cancel = Cancel::new();
+        cancel.cancel();
+        let mut stats = Stats::default();
+        assert_eq!(collect_files(&paths, &cancel, &mut stats), Err(Cancelled));
+        assert!(stats.cancelled);
+        let config = Config {
+            files: paths.to_vec(),
+            cancel: Cancel::new().with_deadline(Instant::now()),
+            ..config
+        };
+        let mut stats = Stats::default();
+        search_files(&config, &mut stats).unwrap();
+        assert_eq!((stats.files_searched, stats.cancelled), (0, true));
+        fs::remove_dir_all(&root).unwrap();
+
+        let args = ["minigrep", "--timeout", "200ms", "rust", "src"];
+        let config = build(&args).unwrap();
+        assert_eq!(config.timeout, Some(Duration::from_millis(200)));
+    }
+}
@@ -0,0 +1,158 @@
+/// 多行模式：key 中可以包含换行符，因此一次匹配可以跨越多行
+use super::matcher::find;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Span<'a> {
+    // 行号从 1 开始，start_line 和 end_line 都包含在内
+    pub start_line: usize,
+    pub end_line: usize,
+    // 从起始行行首到结束行行尾的完整内容，不包含最后的换行符
+    pub text: &'a str,
+}
+
+/// 在整个 contents 中查找 key，返回每次匹配所覆盖的完整行。
+/// 落在相同行上的多次匹配会被合并成一个 Span，避免重复输出同一行。
+pub fn search_multiline<'a>(key: &str, contents: &'a str, ignore_case: bool) -> Vec<Span<'a>> {
+    if key.is_empty() {
+        return Vec::new();
+    }
+    // (起始行号, 结束行号, 起始行首的字节位置, 结束行尾的字节位置)
+    let mut ranges: Vec<(usize, usize, usize, usize)> = Vec::new();
+
+    // 行号是增量计算的，每个字节只会被数一次
+    let mut line = 1;
+    let mut counted = 0;
+    let mut line_of = |idx: usize| {
+        line += contents[counted..idx].matches('\n').count();
+        counted = idx;
+        line
+    };
+
+    let mut pos = 0;
+    while let Some((start, end)) = find(key, contents, pos, ignore_case) {
+        // 匹配以换行符结尾时，结束行是被这个换行符结束的那一行
+        let last = contents[..end]
+            .char_indices()
+            .next_back()
+            .map_or(start, |(i, _)| i);
+        let start_line = line_of(start);
+        let end_line = line_of(last);
+        let line_end = line_end(contents, last);
+
+        match ranges.last_mut() {
+            Some(prev) if start_line <= prev.1 => {
+                prev.1 = prev.1.max(end_line);
+                prev.3 = prev.3.max(line_end);
+            }
+            _ => ranges.push((start_line, end_line, line_start(contents, start), line_end)),
+        }
+        pos = end;
+    }
+
+    ranges
+        .into_iter()
+        .map(|(start_line, end_line, begin, end)| Span {
+            start_line,
+            end_line,
+            text: contents[begin..end].trim_end_matches('\r'),
+        })
+        .collect()
+}
+
+/// 把命令行中的 `\n`、`\t` 和 `\\` 转义成真正的字符，方便在 shell 中输入跨行的 key
+pub fn unescape(key: &str) -> String {
+    let mut result = String::with_capacity(key.len());
+    let mut chars = key.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some('t') => result.push('\t'),
+            Some('\\') => result.push('\\'),
+            Some(other) => {
+                result.push('\\');
+                result.push(other);
+            }
+            None => result.push('\\'),
+        }
+    }
+    result
+}
+
+fn line_start(contents: &str, idx: usize) -> usize {
+    contents[..idx].rfind('\n').map_or(0, |i| i + 1)
+}
+
+fn line_end(contents: &str, idx: usize) -> usize {
+    contents[idx..]
+        .find('\n')
+        .map_or(contents.len(), |i| idx + i)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRACE: &str = "\
+running 1 test
+thread 'main' panicked at src/main.rs:2:5:
+explicit panic
+note: run with `RUST_BACKTRACE=1` environment variable to display a backtrace
+test result: FAILED.";
+
+    #[test]
+    fn match_across_lines() {
+        let key = unescape(r"panicked at src/main.rs:2:5:\nexplicit panic\nnote:");
+        assert_eq!(
+            search_multiline(&key, TRACE, false),
+            vec![Span {
+                start_line: 2,
+                end_line: 4,
+                text: "\
+thread 'main' panicked at src/main.rs:2:5:
+explicit panic
+note: run with `RUST_BACKTRACE=1` environment variable to display a backtrace",
+            }]
+        );
+    }
+
+    #[test]
+    fn match_in_single_line() {
+        let spans = search_multiline("test", TRACE, false);
+        assert_eq!(spans.len(), 2);
+        assert_eq!((spans[0].start_line, spans[0].end_line), (1, 1));
+        assert_eq!(spans[0].text, "running 1 test");
+        assert_eq!((spans[1].start_line, spans[1].end_line), (5, 5));
+    }
+
+    #[test]
+    fn match_case_insensitive() {
+        let key = unescape(r"EXPLICIT PANIC\nNOTE");
+        let spans = search_multiline(&key, TRACE, true);
+        assert_eq!(spans.len(), 1);
+        assert_eq!((spans[0].start_line, spans[0].end_line), (3, 4));
+        assert!(search_multiline(&key, TRACE, false).is_empty());
+    }
+
+    #[test]
+    fn merge_overlapping_spans() {
+        // 第二次匹配从第一次匹配的结束行开始，两者合并为 1-3 行
+        let contents = "x\nyx\ny\nz";
+        assert_eq!(
+            search_multiline("x\ny", contents, false),
+            vec![Span {
+                start_line: 1,
+                end_line: 3,
+                text: "x\nyx\ny",
+            }]
+        );
+    }
+
+    #[test]
+    fn unescape_key() {
+        assert_eq!(unescape(r"a\nb\tc\\n\x"), "a\nb\tc\\n\\x");
+    }
+}
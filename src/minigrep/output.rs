@@ -0,0 +1,207 @@
+/// 搜索结果的输出：默认输出便于阅读的文本，--json 时每个文件输出一行 JSON 对象
+use std::io::{self, Write};
+use std::path::{self, Path};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    #[default]
+    Text,
+    Json,
+}
+
+/// -l 只输出有匹配的文件的路径，-L 只输出没有匹配的文件的路径
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListFiles {
+    WithMatches,
+    WithoutMatch,
+}
+
+/// 单个文件的搜索结果，不同的搜索模式得到的结果形式不同
+#[derive(Debug, PartialEq, Eq)]
+pub enum Found {
+    // 普通模式：匹配的行，或者 only_matching 时匹配的子串
+    Lines(Vec<String>),
+    // 多行模式：(起始行号, 结束行号, 覆盖到的完整内容)
+    Spans(Vec<(usize, usize, String)>),
+    // CSV/TSV 模式：第一条是表头，后面是匹配的记录
+    Records(Vec<Vec<String>>, char),
+}
+
+impl Found {
+    pub fn is_empty(&self) -> bool {
+        match self {
+            Found::Lines(lines) => lines.is_empty(),
+            Found::Spans(spans) => spans.is_empty(),
+            Found::Records(records, _) => records.len() <= 1,
+        }
+    }
+}
+
+/// 文本格式。只搜索一个文件时保持原来的输出，搜索多个文件时在结果前面加上文件路径
+pub fn to_text(found: &Found, path: Option<&Path>) -> String {
+    let prefix = path.map_or(String::new(), |path| format!("[{}] ", path.display()));
+    match found {
+        Found::Lines(lines) => format!("{}Results:{:?}", prefix, lines),
+        Found::Spans(spans) => {
+            let mut text = format!("{}Results:", prefix);
+            for (start_line, end_line, span) in spans {
+                text.push_str(&format!("\n[{}-{}]\n{}", start_line, end_line, span));
+            }
+            text
+        }
+        Found::Records(records, delimiter) => {
+            let lines: Vec<String> = records
+                .iter()
+                .map(|record| super::delimited::write_record(record, *delimiter))
+                .collect();
+            match path {
+                Some(path) => format!("[{}]\n{}", path.display(), lines.join("\n")),
+                None => lines.join("\n"),
+            }
+        }
+    }
+}
+
+/// JSON 格式，一个文件对应一个 JSON 对象
+pub fn to_json(found: &Found, path: &Path) -> String {
+    let path = json_string(&path.to_string_lossy());
+    match found {
+        Found::Lines(lines) => format!("{{\"path\":{},\"results\":{}}}", path, json_array(lines)),
+        Found::Spans(spans) => {
+            let spans: Vec<String> = spans
+                .iter()
+                .map(|(start_line, end_line, text)| {
+                    format!(
+                        "{{\"start_line\":{},\"end_line\":{},\"text\":{}}}",
+                        start_line,
+                        end_line,
+                        json_string(text)
+                    )
+                })
+                .collect();
+            format!("{{\"path\":{},\"spans\":[{}]}}", path, spans.join(","))
+        }
+        Found::Records(records, _) => {
+            let records: Vec<String> = records.iter().map(|r| json_array(r)).collect();
+            format!("{{\"path\":{},\"records\":[{}]}}", path, records.join(","))
+        }
+    }
+}
+
+/// 路径的原始字节。Unix 上的文件名可以不是 UTF-8，直接取 OsStr 中的字节而不经过 String 转换，
+/// 这样输出的路径可以原样交给 xargs 等工具；设置了 separator 时替换其中的路径分隔符
+pub fn path_bytes(path: &Path, separator: Option<u8>) -> Vec<u8> {
+    #[cfg(unix)]
+    let mut bytes = {
+        use std::os::unix::ffi::OsStrExt;
+        path.as_os_str().as_bytes().to_vec()
+    };
+    #[cfg(not(unix))]
+    let mut bytes = path.to_string_lossy().into_owned().into_bytes();
+
+    if let Some(separator) = separator {
+        for b in bytes.iter_mut().filter(|b| path::is_separator(**b as char)) {
+            *b = separator;
+        }
+    }
+    bytes
+}
+
+/// 输出 -l/-L 中的一个路径，null 时以 NUL 结尾，否则以换行结尾
+pub fn write_path<W: Write>(
+    out: &mut W,
+    path: &Path,
+    separator: Option<u8>,
+    null: bool,
+) -> io::Result<()> {
+    out.write_all(&path_bytes(path, separator))?;
+    out.write_all(if null { b"\0" } else { b"\n" })
+}
+
+/// 把字符串编码成 JSON 字符串字面量
+pub fn json_string(s: &str) -> String {
+    let mut result = String::with_capacity(s.len() + 2);
+    result.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            c if (c as u32) < 0x20 => result.push_str(&format!("\\u{:04x}", c as u32)),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+pub fn json_array(items: &[String]) -> String {
+    let items: Vec<String> = items.iter().map(|item| json_string(item)).collect();
+    format!("[{}]", items.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_json_string() {
+        assert_eq!(json_string("a\"b\\c\n"), r#""a\"b\\c\n""#);
+        assert_eq!(json_string("你好\u{1}"), "\"你好\\u0001\"");
+    }
+
+    #[test]
+    fn found_to_text() {
+        let found = Found::Lines(vec!["Rust:".to_string()]);
+        assert_eq!(to_text(&found, None), "Results:[\"Rust:\"]");
+        assert_eq!(
+            to_text(&found, Some(Path::new("a.txt"))),
+            "[a.txt] Results:[\"Rust:\"]"
+        );
+
+        let found = Found::Spans(vec![(1, 2, "a\nb".to_string())]);
+        assert_eq!(to_text(&found, None), "Results:\n[1-2]\na\nb");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn raw_path_bytes() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        // 含有空格、换行和非 UTF-8 字节的文件名原样输出
+        let path = Path::new(OsStr::from_bytes(b"dir/a b\n\xff.txt"));
+        assert_eq!(path_bytes(path, None), b"dir/a b\n\xff.txt");
+        assert_eq!(path_bytes(path, Some(b'\\')), b"dir\\a b\n\xff.txt");
+
+        let mut out = Vec::new();
+        write_path(&mut out, path, None, true).unwrap();
+        write_path(&mut out, Path::new("b"), None, false).unwrap();
+        assert_eq!(out, b"dir/a b\n\xff.txt\0b\n");
+    }
+
+    #[test]
+    fn found_to_json() {
+        let path = Path::new("a.txt");
+        let found = Found::Lines(vec!["Rust:".to_string()]);
+        assert_eq!(
+            to_json(&found, path),
+            r#"{"path":"a.txt","results":["Rust:"]}"#
+        );
+
+        let found = Found::Spans(vec![(1, 2, "a\nb".to_string())]);
+        assert_eq!(
+            to_json(&found, path),
+            r#"{"path":"a.txt","spans":[{"start_line":1,"end_line":2,"text":"a\nb"}]}"#
+        );
+
+        let records = vec![vec!["name".to_string()], vec!["lisi".to_string()]];
+        let found = Found::Records(records, ',');
+        assert_eq!(
+            to_json(&found, path),
+            r#"{"path":"a.txt","records":[["name"],["lisi"]]}"#
+        );
+    }
+}
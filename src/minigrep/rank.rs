@@ -0,0 +1,380 @@
+/// 排序模式：把 key 拆成多个词，用 BM25 给每个文件（或段落）打分，输出得分最高的前 N 个。
+/// 文档和查询按词的边界切分，词只与完整的词匹配（rust 不会匹配 trust）：这是有意与其他搜索模式不同的地方，
+/// BM25 的词频和文档频率按词统计，按子串统计会让 trust 这样的词抬高 rust 的得分。
+/// 忽略大小写的规则与其他模式相同，都使用 matcher::fold_case。
+/// 命令行每次运行都重新建立索引；交互模式（-I --rank）中索引只在第一次查询时建立，之后的查询都复用它
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::output::{json_string, Format};
+use super::stats::Stats;
+use super::{collect_files, matcher, Config};
+
+// BM25 的两个常用参数：k1 控制词频饱和的速度，b 控制文档长度归一化的程度
+const K1: f64 = 1.2;
+const B: f64 = 0.75;
+
+/// 参与排序的一个文档：整个文件，或者文件中以空行分隔的一个段落
+#[derive(Debug, Clone, PartialEq)]
+pub struct Document {
+    pub path: PathBuf,
+    // 文档在文件中的起始行号，从 1 开始
+    pub line: usize,
+    pub text: String,
+    // 文档长度，即 tokenize 切分出的词数
+    len: usize,
+}
+
+/// 把文本切分成词：连续的字母、数字和下划线组成一个词，其余字符都是分隔符
+pub fn tokenize(text: &str) -> impl Iterator<Item = &str> {
+    text.split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|word| !word.is_empty())
+}
+
+impl Document {
+    pub fn new(path: PathBuf, line: usize, text: String) -> Self {
+        let len = tokenize(&text).count();
+        Document {
+            path,
+            line,
+            text,
+            len,
+        }
+    }
+}
+
+/// 把文件内容按空行拆成段落
+pub fn paragraphs(path: &Path, contents: &str) -> Vec<Document> {
+    let mut docs = Vec::new();
+    let mut start = None;
+    let mut text = String::new();
+    for (i, line) in contents.lines().enumerate() {
+        if line.trim().is_empty() {
+            if let Some(line) = start.take() {
+                docs.push(Document::new(
+                    path.to_path_buf(),
+                    line,
+                    std::mem::take(&mut text),
+                ));
+            }
+            continue;
+        }
+        if start.is_none() {
+            start = Some(i + 1);
+        } else {
+            text.push('\n');
+        }
+        text.push_str(line);
+    }
+    if let Some(line) = start {
+        docs.push(Document::new(path.to_path_buf(), line, text));
+    }
+    docs
+}
+
+/// 文档集合的倒排索引，建立时对每个文档分词一次，之后的每次查询只需要查表
+#[derive(Debug)]
+pub struct Index {
+    docs: Vec<Document>,
+    avg_len: f64,
+    // 词 -> [(文档编号, 词频)]，按文档编号排列。
+    // 原样的词和转换成小写的词各建一份，分别用于区分大小写和忽略大小写的查询
+    exact: HashMap<String, Vec<(usize, usize)>>,
+    folded: HashMap<String, Vec<(usize, usize)>>,
+}
+
+impl Index {
+    pub fn new(docs: Vec<Document>) -> Self {
+        let total: usize = docs.iter().map(|doc| doc.len).sum();
+        let avg_len = if docs.is_empty() {
+            0.0
+        } else {
+            total as f64 / docs.len() as f64
+        };
+
+        let mut exact = HashMap::new();
+        let mut folded = HashMap::new();
+        for (id, doc) in docs.iter().enumerate() {
+            let mut counts: HashMap<&str, usize> = HashMap::new();
+            for word in tokenize(&doc.text) {
+                *counts.entry(word).or_default() += 1;
+            }
+            let mut folded_counts: HashMap<String, usize> = HashMap::new();
+            for (word, tf) in counts {
+                *folded_counts.entry(matcher::fold_case(word)).or_default() += tf;
+                exact
+                    .entry(word.to_string())
+                    .or_insert_with(Vec::new)
+                    .push((id, tf));
+            }
+            for (word, tf) in folded_counts {
+                folded.entry(word).or_insert_with(Vec::new).push((id, tf));
+            }
+        }
+
+        Index {
+            docs,
+            avg_len,
+            exact,
+            folded,
+        }
+    }
+
+    pub fn docs(&self) -> &[Document] {
+        &self.docs
+    }
+
+    /// 返回包含 term 这个词的文档及词频
+    pub fn postings(&self, term: &str, ignore_case: bool) -> &[(usize, usize)] {
+        let postings = if ignore_case {
+            self.folded.get(&matcher::fold_case(term))
+        } else {
+            self.exact.get(term)
+        };
+        postings.map_or(&[], Vec::as_slice)
+    }
+
+    /// 用 BM25 给每个文档打分，返回得分最高的 top_n 个 (文档, 得分)，得分相同时保持文档原来的顺序
+    pub fn rank(&self, key: &str, ignore_case: bool, top_n: usize) -> Vec<(&Document, f64)> {
+        let n = self.docs.len() as f64;
+        let mut terms: Vec<String> = tokenize(key)
+            .map(|term| {
+                if ignore_case {
+                    matcher::fold_case(term)
+                } else {
+                    term.to_string()
+                }
+            })
+            .collect();
+        terms.sort();
+        terms.dedup();
+
+        let avg_len = self.avg_len;
+        let mut scores = vec![0.0; self.docs.len()];
+        for term in &terms {
+            let postings = self.postings(term, ignore_case);
+            let df = postings.len() as f64;
+            let idf = ((n - df + 0.5) / (df + 0.5) + 1.0).ln();
+            for &(id, tf) in postings {
+                let tf = tf as f64;
+                let norm = if avg_len > 0.0 {
+                    self.docs[id].len as f64 / avg_len
+                } else {
+                    1.0
+                };
+                scores[id] += idf * tf * (K1 + 1.0) / (tf + K1 * (1.0 - B + B * norm));
+            }
+        }
+
+        let mut ranked: Vec<(usize, f64)> = scores
+            .into_iter()
+            .enumerate()
+            .filter(|(_, score)| *score > 0.0)
+            .collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+        ranked.truncate(top_n);
+        ranked
+            .into_iter()
+            .map(|(id, score)| (&self.docs[id], score))
+            .collect()
+    }
+}
+
+/// 把一个文件按 paragraphs 拆分成文档，不拆分时整个文件就是一个文档
+pub fn documents(path: PathBuf, contents: String, paragraphs: bool) -> Vec<Document> {
+    if paragraphs {
+        self::paragraphs(&path, &contents)
+    } else {
+        vec![Document::new(path, 1, contents)]
+    }
+}
+
+/// 加载 config.files 中的文件，按 config.paragraphs 拆分成文档
+pub fn load(config: &Config, stats: &mut Stats) -> Vec<Document> {
+    let mut docs = Vec::new();
+    let Ok(files) = collect_files(&config.files, &config.cancel, stats) else {
+        return docs;
+    };
+    for path in files {
+        // 取消时只对已经加载的文档排序
+        if super::cancelled(config, stats) {
+            break;
+        }
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(err) => {
+                stats.files_skipped += 1;
+                eprintln!("Skipped [{}]: {}", path.display(), err);
+                continue;
+            }
+        };
+        stats.files_searched += 1;
+        stats.bytes_scanned += contents.len();
+        stats.lines_scanned += contents.lines().count();
+
+        docs.extend(documents(path, contents, config.paragraphs));
+    }
+    docs
+}
+
+/// 格式化一条排序结果
+pub fn format_result(doc: &Document, score: f64, paragraphs: bool, format: Format) -> String {
+    match (format, paragraphs) {
+        (Format::Text, false) => format!("{:.4}  {}", score, doc.path.display()),
+        (Format::Text, true) => format!(
+            "{:.4}  [{}:{}]\n{}",
+            score,
+            doc.path.display(),
+            doc.line,
+            doc.text
+        ),
+        (Format::Json, false) => format!(
+            "{{\"path\":{},\"score\":{:.4}}}",
+            json_string(&doc.path.to_string_lossy()),
+            score
+        ),
+        (Format::Json, true) => format!(
+            "{{\"path\":{},\"line\":{},\"score\":{:.4},\"text\":{}}}",
+            json_string(&doc.path.to_string_lossy()),
+            doc.line,
+            score,
+            json_string(&doc.text)
+        ),
+    }
+}
+
+pub fn run(config: &Config, top_n: usize, stats: &mut Stats) -> Result<(), Box<dyn Error>> {
+    let index = Index::new(load(config, stats));
+    for (doc, score) in index.rank(&config.key, config.ignore_case, top_n) {
+        // 每个输出的文档（得分都大于 0）计为一次匹配
+        stats.matched_lines += 1;
+        stats.matches += 1;
+        println!(
+            "{}",
+            format_result(doc, score, config.paragraphs, config.format)
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn docs(texts: &[&str]) -> Vec<Document> {
+        texts
+            .iter()
+            .enumerate()
+            .map(|(i, text)| {
+                Document::new(PathBuf::from(format!("{}.txt", i)), 1, text.to_string())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn rank_by_bm25() {
+        let index = Index::new(docs(&[
+            "rust is fast",
+            "rust rust rust safe and fast",
+            "go is simple",
+            "safe",
+        ]));
+
+        let ranked = index.rank("rust safe", false, 10);
+        let paths: Vec<_> = ranked
+            .iter()
+            .map(|(doc, _)| doc.path.to_str().unwrap())
+            .collect();
+        // 1.txt 同时包含两个词且 rust 出现多次，得分最高；3.txt 很短，safe 的权重比 0.txt 的 rust 更高
+        assert_eq!(paths, vec!["1.txt", "3.txt", "0.txt"]);
+        assert!(ranked[0].1 > ranked[1].1 && ranked[1].1 > ranked[2].1);
+
+        let ranked = index.rank("rust safe", false, 1);
+        assert_eq!(ranked.len(), 1);
+        assert!(index.rank("java", false, 10).is_empty());
+    }
+
+    #[test]
+    fn rare_terms_weigh_more() {
+        let index = Index::new(docs(&["common rare", "common", "common", "common"]));
+        let common = index.rank("common", false, 10)[0].1;
+        let rare = index.rank("rare", false, 10)[0].1;
+        assert!(rare > common);
+    }
+
+    #[test]
+    fn word_postings() {
+        let index = Index::new(docs(&["Rust rust, trust rusty", "RUST", "trust"]));
+        assert_eq!(index.postings("rust", false), &[(0, 1)]);
+        assert_eq!(index.postings("rust", true), &[(0, 2), (1, 1)]);
+        assert_eq!(index.postings("RUST", true), &[(0, 2), (1, 1)]);
+        assert_eq!(index.postings("trust", false), &[(0, 1), (2, 1)]);
+        assert!(index.postings("rus", false).is_empty());
+        assert_eq!(index.docs()[0].len, 4);
+
+        // rust 只匹配完整的词，只包含 trust 的文档不参与排序；查询中的标点同样是分隔符
+        let ranked = index.rank("rust,", true, 10);
+        assert_eq!(ranked.len(), 2);
+        assert!(ranked.iter().all(|(doc, _)| doc.path != Path::new("2.txt")));
+    }
+
+    #[test]
+    fn ranked_stats() {
+        let config = Config {
+            key: "nobody frog".to_string(),
+            files: vec!["src/examples".to_string()],
+            ..Default::default()
+        };
+        let mut stats = Stats::default();
+        run(&config, 10, &mut stats).unwrap();
+        // 只有 test.txt 包含查询中的词，minigrep.rs 得分为 0，不计入匹配
+        assert_eq!(stats.files_searched, 2);
+        assert_eq!((stats.matched_lines, stats.matches), (1, 1));
+
+        let config = Config {
+            paragraphs: true,
+            ..config
+        };
+        let mut stats = Stats::default();
+        run(&config, 1, &mut stats).unwrap();
+        assert_eq!((stats.matched_lines, stats.matches), (1, 1));
+    }
+
+    #[test]
+    fn fold_case_like_search() {
+        // 与普通搜索的 -i 使用同样的规则，词尾的 Σ 与 σ 匹配
+        let index = Index::new(docs(&["οδοσ", "δρομος"]));
+        let ranked = index.rank("ΟΔΟΣ", true, 10);
+        assert_eq!(ranked.len(), 1);
+        assert_eq!(ranked[0].0.text, "οδοσ");
+        assert!(index.rank("ΟΔΟΣ", false, 10).is_empty());
+    }
+
+    #[test]
+    fn split_words() {
+        let words: Vec<_> = tokenize("fn main() { let snake_case = 1.5; } 青蛙，呱噪").collect();
+        assert_eq!(
+            words,
+            vec!["fn", "main", "let", "snake_case", "1", "5", "青蛙", "呱噪"]
+        );
+    }
+
+    #[test]
+    fn split_paragraphs() {
+        let docs = paragraphs(Path::new("a.txt"), "\nfirst\nline\n\n\nsecond\n");
+        assert_eq!(docs.len(), 2);
+        assert_eq!((docs[0].line, docs[0].text.as_str()), (2, "first\nline"));
+        assert_eq!((docs[1].line, docs[1].text.as_str()), (6, "second"));
+
+        assert_eq!(
+            format_result(&docs[1], 1.5, true, Format::Json),
+            r#"{"path":"a.txt","line":6,"score":1.5000,"text":"second"}"#
+        );
+        assert_eq!(
+            format_result(&docs[1], 1.5, false, Format::Text),
+            "1.5000  a.txt"
+        );
+    }
+}
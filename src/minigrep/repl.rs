@@ -0,0 +1,290 @@
+/// 交互模式：文件只加载一次，之后每输入一行就执行一次查询，
+/// 以 `:` 开头的输入是命令，可以切换大小写、上下文行数和输出格式
+use std::error::Error;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+
+use super::output::{self, Format};
+use super::rank::{self, Index};
+use super::stats::Stats;
+use super::{collect_files, multiline, search_contents, Config};
+
+const HELP: &str = "\
+Commands:
+  :i                 toggle case sensitivity
+  :context N         show N lines of context around each match
+  :format text|json  change the output format
+  :history           list the queries of this session
+  !N                 run query N of the history again
+  :help              show this message
+  :quit              exit
+Any other input is searched for as a key.";
+
+pub struct Repl {
+    config: Config,
+    // 已经加载到内存中的文件
+    corpus: Vec<(PathBuf, String)>,
+    // 排序模式下第一次查询时才建立索引，之后的查询都复用它
+    index: Option<Index>,
+    history: Vec<String>,
+}
+
+impl Repl {
+    /// 加载 config.files 中的所有文件，无法读取的文件会被跳过
+    pub fn load(config: Config) -> Result<Repl, Box<dyn Error>> {
+        let mut stats = Stats::default();
+        let mut corpus = Vec::new();
+        for path in collect_files(&config.files, &config.cancel, &mut stats)? {
+            match fs::read_to_string(&path) {
+                Ok(contents) => corpus.push((path, contents)),
+                Err(err) => eprintln!("Skipped [{}]: {}", path.display(), err),
+            }
+        }
+        if corpus.is_empty() {
+            return Err("No file was loaded".into());
+        }
+
+        Ok(Repl {
+            config,
+            corpus,
+            index: None,
+            history: Vec::new(),
+        })
+    }
+
+    pub fn history(&self) -> &[String] {
+        &self.history
+    }
+
+    /// 从 input 逐行读取并执行，直到输入结束或者遇到 :quit
+    pub fn run<R: BufRead, W: Write>(&mut self, input: R, mut out: W) -> io::Result<()> {
+        write!(out, "> ")?;
+        out.flush()?;
+        for line in input.lines() {
+            if !self.eval(&line?, &mut out)? {
+                break;
+            }
+            write!(out, "> ")?;
+            out.flush()?;
+        }
+        Ok(())
+    }
+
+    /// 执行一行输入，返回 false 表示需要退出
+    pub fn eval<W: Write>(&mut self, input: &str, out: &mut W) -> io::Result<bool> {
+        if input.trim().is_empty() {
+            return Ok(true);
+        }
+
+        if let Some(n) = input.trim().strip_prefix('!') {
+            let query = n
+                .parse::<usize>()
+                .ok()
+                .and_then(|n| n.checked_sub(1))
+                .and_then(|i| self.history.get(i))
+                .cloned();
+            match query {
+                Some(query) => return self.eval(&query, out),
+                None => writeln!(out, "No such query in history: {}", n)?,
+            }
+            return Ok(true);
+        }
+
+        if let Some(command) = input.trim().strip_prefix(':') {
+            let mut parts = command.split_whitespace();
+            match (parts.next(), parts.next()) {
+                (Some("q" | "quit"), _) => return Ok(false),
+                (Some("i" | "ignore_case"), _) => {
+                    self.config.ignore_case = !self.config.ignore_case;
+                    writeln!(out, "ignore_case: {}", self.config.ignore_case)?;
+                }
+                (Some("context"), Some(n)) => match n.parse() {
+                    Ok(n) => {
+                        self.config.context = n;
+                        writeln!(out, "context: {}", n)?;
+                    }
+                    Err(_) => writeln!(out, "Invalid context: {}", n)?,
+                },
+                (Some("format"), Some("text")) => {
+                    self.config.format = Format::Text;
+                    writeln!(out, "format: text")?;
+                }
+                (Some("format"), Some("json")) => {
+                    self.config.format = Format::Json;
+                    writeln!(out, "format: json")?;
+                }
+                (Some("history"), _) => {
+                    for (i, query) in self.history.iter().enumerate() {
+                        writeln!(out, "{:>4}  {}", i + 1, query)?;
+                    }
+                }
+                (Some("help"), _) => writeln!(out, "{}", HELP)?,
+                _ => writeln!(out, "Unknown command: {}, type :help for help", input)?,
+            }
+            return Ok(true);
+        }
+
+        self.history.push(input.to_string());
+        self.search(input, out)?;
+        Ok(true)
+    }
+
+    fn search<W: Write>(&mut self, key: &str, out: &mut W) -> io::Result<()> {
+        self.config.key = if self.config.multiline {
+            multiline::unescape(key)
+        } else {
+            key.to_string()
+        };
+
+        if let Some(top_n) = self.config.rank {
+            let corpus = &self.corpus;
+            let paragraphs = self.config.paragraphs;
+            let index = self.index.get_or_insert_with(|| {
+                Index::new(
+                    corpus
+                        .iter()
+                        .flat_map(|(path, contents)| {
+                            rank::documents(path.clone(), contents.clone(), paragraphs)
+                        })
+                        .collect(),
+                )
+            });
+            for (doc, score) in index.rank(&self.config.key, self.config.ignore_case, top_n) {
+                let result = rank::format_result(doc, score, paragraphs, self.config.format);
+                writeln!(out, "{}", result)?;
+            }
+            return Ok(());
+        }
+
+        let show_path = self.corpus.len() > 1;
+        let mut stats = Stats::default();
+        for (path, contents) in &self.corpus {
+            let found = match search_contents(&self.config, contents, &mut stats) {
+                Ok(found) => found,
+                Err(err) => {
+                    writeln!(out, "Error [{}]: {}", path.display(), err)?;
+                    continue;
+                }
+            };
+            if show_path && found.is_empty() {
+                continue;
+            }
+            match self.config.format {
+                Format::Text => writeln!(
+                    out,
+                    "{}",
+                    output::to_text(&found, show_path.then_some(path.as_path()))
+                )?,
+                Format::Json => writeln!(out, "{}", output::to_json(&found, path))?,
+            }
+        }
+
+        if self.config.stats {
+            writeln!(out, "{}", stats)?;
+        }
+        Ok(())
+    }
+}
+
+/// 在标准输入输出上运行交互模式
+pub fn run(config: Config) -> Result<(), Box<dyn Error>> {
+    let mut repl = Repl::load(config)?;
+    println!("{} file(s) loaded, type :help for help", repl.corpus.len());
+    repl.run(io::stdin().lock(), io::stdout())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(repl: &mut Repl, input: &str) -> String {
+        let mut out = Vec::new();
+        assert!(repl.eval(input, &mut out).unwrap());
+        String::from_utf8(out).unwrap()
+    }
+
+    fn load() -> Repl {
+        Repl::load(Config {
+            files: vec!["src/examples/test.txt".to_string()],
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn query_and_toggle() {
+        let mut repl = load();
+        assert_eq!(
+            eval(&mut repl, "Who"),
+            "Results:[\"I'm nobody! Who are you?\"]\n"
+        );
+        assert_eq!(eval(&mut repl, "how"), "Results:[]\n");
+
+        assert_eq!(eval(&mut repl, ":i"), "ignore_case: true\n");
+        assert_eq!(
+            eval(&mut repl, "how"),
+            "Results:[\"How dreary to be somebody!\", \"How public, like a frog\"]\n"
+        );
+
+        assert_eq!(eval(&mut repl, ":context 1"), "context: 1\n");
+        assert_eq!(
+            eval(&mut repl, "frog"),
+            "Results:[\"因为这里属于没劲的大人物！\", \"How public, like a frog\", \"他们就像青蛙一样呱噪，\"]\n"
+        );
+
+        assert_eq!(eval(&mut repl, ":format json"), "format: json\n");
+        assert_eq!(
+            eval(&mut repl, "bog"),
+            "{\"path\":\"src/examples/test.txt\",\"results\":[\"成天将自己的大名\",\"To an admiring bog!\",\"传遍整个无聊的沼泽！\"]}\n"
+        );
+    }
+
+    #[test]
+    fn ranked_query() {
+        let mut repl = Repl::load(Config {
+            files: vec!["src/examples".to_string()],
+            rank: Some(1),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(eval(&mut repl, "you nobody").lines().count(), 1);
+        assert!(eval(&mut repl, "you nobody").ends_with("src/examples/test.txt\n"));
+        assert!(eval(&mut repl, "config").ends_with("src/examples/minigrep.rs\n"));
+        assert_eq!(eval(&mut repl, "java"), "");
+    }
+
+    #[test]
+    fn history() {
+        let mut repl = load();
+        eval(&mut repl, "nobody");
+        eval(&mut repl, ":i");
+        eval(&mut repl, "frog");
+        assert_eq!(repl.history(), ["nobody", "frog"]);
+        assert_eq!(eval(&mut repl, ":history"), "   1  nobody\n   2  frog\n");
+
+        assert_eq!(
+            eval(&mut repl, "!2"),
+            "Results:[\"How public, like a frog\"]\n"
+        );
+        assert_eq!(repl.history().len(), 3);
+        assert_eq!(eval(&mut repl, "!9"), "No such query in history: 9\n");
+    }
+
+    #[test]
+    fn run_until_quit() {
+        let mut repl = load();
+        let input = "nobody\n:quit\nfrog\n";
+        let mut out = Vec::new();
+        repl.run(input.as_bytes(), &mut out).unwrap();
+        assert_eq!(repl.history(), ["nobody"]);
+        assert!(String::from_utf8(out).unwrap().ends_with("> "));
+
+        assert!(Repl::load(Config {
+            files: vec!["src/examples/none.txt".to_string()],
+            ..Default::default()
+        })
+        .is_err());
+    }
+}
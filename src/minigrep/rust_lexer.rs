@@ -0,0 +1,270 @@
+/// 一个轻量的 Rust 词法分析器：不做完整的语法分析，只把源码切分成代码、注释和字符串字面量三类区间，
+/// 用来把匹配限制在其中的某几类里，比如只在注释中查找
+use std::ops::Range;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Class {
+    Code,
+    // 行注释、块注释（可以嵌套），也包括文档注释
+    Comment,
+    // 字符串、原始字符串、字节字符串以及字符字面量
+    String,
+}
+
+impl FromStr for Class {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "code" => Ok(Class::Code),
+            "comment" | "comments" => Ok(Class::Comment),
+            "string" | "strings" => Ok(Class::String),
+            _ => Err("Syntax class must be code, comment or string"),
+        }
+    }
+}
+
+/// 解析 `comment,string` 这样以逗号分隔的列表
+pub fn parse_classes(list: &str) -> Result<Vec<Class>, &'static str> {
+    list.split(',').map(|class| class.trim().parse()).collect()
+}
+
+/// 把源码切分成首尾相接、覆盖全部内容的区间
+pub fn classify(src: &str) -> Vec<(Range<usize>, Class)> {
+    let bytes = src.as_bytes();
+    let mut spans = Vec::new();
+    let mut code_start = 0;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let (class, end) = match bytes[i] {
+            b'/' if bytes.get(i + 1) == Some(&b'/') => (Class::Comment, line_comment_end(bytes, i)),
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                (Class::Comment, block_comment_end(bytes, i))
+            }
+            b'"' => (Class::String, string_end(bytes, i + 1)),
+            b'\'' => match char_end(src, i) {
+                Some(end) => (Class::String, end),
+                // 生命周期标注，例如 'a，属于代码
+                None => {
+                    i += 1;
+                    continue;
+                }
+            },
+            b'r' | b'b' if !is_ident(bytes, i) => match prefixed_literal_end(src, i) {
+                Some(end) => (Class::String, end),
+                None => {
+                    i += 1;
+                    continue;
+                }
+            },
+            _ => {
+                i += 1;
+                continue;
+            }
+        };
+
+        if code_start < i {
+            spans.push((code_start..i, Class::Code));
+        }
+        spans.push((i..end, class));
+        code_start = end;
+        i = end;
+    }
+
+    if code_start < bytes.len() {
+        spans.push((code_start..bytes.len(), Class::Code));
+    }
+    spans
+}
+
+/// 判断区间 [start, end) 是否完全落在 classes 允许的区间内
+pub fn is_within(
+    spans: &[(Range<usize>, Class)],
+    start: usize,
+    end: usize,
+    classes: &[Class],
+) -> bool {
+    // spans 按位置排好序并且首尾相接，二分查找包含 start 的区间
+    let first = spans.partition_point(|(range, _)| range.end <= start);
+    spans[first..]
+        .iter()
+        .take_while(|(range, _)| range.start < end.max(start + 1))
+        .all(|(_, class)| classes.contains(class))
+}
+
+// 前一个字节是标识符的一部分，说明 r/b 是标识符中间的字母，而不是字面量的前缀
+fn is_ident(bytes: &[u8], i: usize) -> bool {
+    i > 0 && (bytes[i - 1].is_ascii_alphanumeric() || bytes[i - 1] == b'_')
+}
+
+// 行注释到行尾结束，不包含换行符
+fn line_comment_end(bytes: &[u8], start: usize) -> usize {
+    bytes[start..]
+        .iter()
+        .position(|&b| b == b'\n')
+        .map_or(bytes.len(), |i| start + i)
+}
+
+// 块注释可以嵌套，没有闭合时一直到文件末尾
+fn block_comment_end(bytes: &[u8], start: usize) -> usize {
+    let mut depth = 0;
+    let mut i = start;
+    while i + 1 < bytes.len() {
+        match (bytes[i], bytes[i + 1]) {
+            (b'/', b'*') => {
+                depth += 1;
+                i += 2;
+            }
+            (b'*', b'/') => {
+                depth -= 1;
+                i += 2;
+                if depth == 0 {
+                    return i;
+                }
+            }
+            _ => i += 1,
+        }
+    }
+    bytes.len()
+}
+
+// 从开头的引号之后开始，找到没有被转义的结束引号
+fn string_end(bytes: &[u8], mut i: usize) -> usize {
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'"' => return i + 1,
+            _ => i += 1,
+        }
+    }
+    bytes.len()
+}
+
+// 字符字面量：'a'、'\n'、'\''、'\u{4f60}'、'你'；不是字符字面量时返回 None
+fn char_end(src: &str, start: usize) -> Option<usize> {
+    let rest = &src[start + 1..];
+    match rest.chars().next()? {
+        // 跳过反斜杠和被转义的字符，'\'' 中间的引号不是结束的引号
+        '\\' => rest.get(2..)?.find('\'').map(|i| start + 1 + 2 + i + 1),
+        '\'' => None,
+        c => {
+            let next = c.len_utf8();
+            (rest.as_bytes().get(next) == Some(&b'\'')).then_some(start + 1 + next + 1)
+        }
+    }
+}
+
+// r"..."、r#"..."#、b"..."、br"..."、b'a' 这些带前缀的字面量
+fn prefixed_literal_end(src: &str, start: usize) -> Option<usize> {
+    let bytes = src.as_bytes();
+    let mut i = start;
+    if bytes[i] == b'b' {
+        i += 1;
+        match bytes.get(i) {
+            Some(b'"') => return Some(string_end(bytes, i + 1)),
+            Some(b'\'') => return char_end(src, i),
+            Some(b'r') => {}
+            _ => return None,
+        }
+    }
+
+    // 原始字符串：r 后面跟着若干个 #，然后是引号，结束时需要同样数量的 #
+    i += 1;
+    let hashes = bytes[i..].iter().take_while(|&&b| b == b'#').count();
+    i += hashes;
+    if bytes.get(i) != Some(&b'"') {
+        return None;
+    }
+    let closing = format!("\"{}", "#".repeat(hashes));
+    Some(
+        src[i + 1..]
+            .find(&closing)
+            .map_or(bytes.len(), |j| i + 1 + j + closing.len()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 把每个区间还原成 (类别, 文本)，忽略空白的代码区间，方便断言
+    fn tokens(src: &str) -> Vec<(Class, &str)> {
+        classify(src)
+            .into_iter()
+            .map(|(range, class)| (class, &src[range]))
+            .filter(|(class, text)| *class != Class::Code || !text.trim().is_empty())
+            .collect()
+    }
+
+    #[test]
+    fn comments_and_strings() {
+        let src = r#"/// 文档注释
+let s = "a \" // not a comment"; // 行注释
+/* 块注释 /* 嵌套 */ 结束 */ let c = '"';"#;
+        assert_eq!(
+            tokens(src),
+            vec![
+                (Class::Comment, "/// 文档注释"),
+                (Class::Code, "\nlet s = "),
+                (Class::String, r#""a \" // not a comment""#),
+                (Class::Code, "; "),
+                (Class::Comment, "// 行注释"),
+                (Class::Comment, "/* 块注释 /* 嵌套 */ 结束 */"),
+                (Class::Code, " let c = "),
+                (Class::String, "'\"'"),
+                (Class::Code, ";"),
+            ]
+        );
+    }
+
+    #[test]
+    fn raw_strings_and_lifetimes() {
+        let src =
+            r###"fn f<'a>(x: &'a str) -> &'a str { br##"raw "# str"## ; r"x"; b'\''; '你'; x }"###;
+        let strings: Vec<&str> = tokens(src)
+            .into_iter()
+            .filter(|(class, _)| *class == Class::String)
+            .map(|(_, text)| text)
+            .collect();
+        assert_eq!(
+            strings,
+            vec![r###"br##"raw "# str"##"###, r#"r"x""#, r"b'\''", "'你'"]
+        );
+
+        // 标识符中的 r 和 b 不是字面量前缀
+        let src = r#"let bar = "x"; let br = 1;"#;
+        assert_eq!(tokens(src)[1], (Class::String, "\"x\""));
+    }
+
+    #[test]
+    fn spans_cover_source() {
+        let src = "let a = 1; // one\n\"two\"";
+        let spans = classify(src);
+        assert_eq!(spans.first().unwrap().0.start, 0);
+        assert_eq!(spans.last().unwrap().0.end, src.len());
+        assert!(spans.windows(2).all(|w| w[0].0.end == w[1].0.start));
+
+        let comment = src.find("one").unwrap();
+        assert!(is_within(&spans, comment, comment + 3, &[Class::Comment]));
+        assert!(!is_within(&spans, comment, comment + 3, &[Class::Code]));
+        // 跨越了代码和注释的匹配只有两类都允许时才算
+        assert!(!is_within(&spans, 8, comment + 3, &[Class::Comment]));
+        assert!(is_within(
+            &spans,
+            8,
+            comment + 3,
+            &[Class::Code, Class::Comment]
+        ));
+    }
+
+    #[test]
+    fn parse_class_list() {
+        assert_eq!(
+            parse_classes("comment, string").unwrap(),
+            vec![Class::Comment, Class::String]
+        );
+        assert!(parse_classes("doc").is_err());
+    }
+}
@@ -0,0 +1,335 @@
+/// HTTP 服务模式：在 tokio 上提供一个很小的 JSON 接口 `GET /search?q=nobody&ignore_case=1`，对配置好的目录进行搜索。
+/// 响应使用 chunked 编码逐个文件流式返回，每行一个 JSON 对象（与 --json 的输出相同），
+/// 最后一行是本次请求的统计信息。
+use std::collections::HashMap;
+use std::error::Error;
+use std::future::Future;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::Semaphore;
+use tokio::time::timeout;
+
+use super::output::{self, json_string};
+use super::stats::Stats;
+use super::{collect_files, search_path, Config};
+
+/// 对请求的各种限制，防止单个请求占用过多资源
+#[derive(Debug, Clone)]
+pub struct Limits {
+    // 请求头（包括请求行）的最大字节数
+    pub max_request_bytes: usize,
+    pub max_query_len: usize,
+    // 每个请求最多返回多少个匹配的行
+    pub max_results: usize,
+    // 同时处理的连接数，超过时返回 503。
+    // 返回 503 的连接也要读掉请求，同样最多只有这么多个，再多的连接直接关闭
+    pub max_connections: usize,
+    // 读取请求的超时时间
+    pub read_timeout: Duration,
+    // 每次写入响应的超时时间，客户端一直不读取时关闭连接，释放占用的许可
+    pub write_timeout: Duration,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            max_request_bytes: 8 * 1024,
+            max_query_len: 256,
+            max_results: 1000,
+            max_connections: 64,
+            read_timeout: Duration::from_secs(5),
+            write_timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+/// 在 config.serve 指定的地址上启动服务，搜索 config.files 中的文件和目录
+pub async fn run(config: Config) -> Result<(), Box<dyn Error>> {
+    let addr = config.serve.clone().unwrap_or_default();
+    let listener = TcpListener::bind(&addr).await?;
+    println!("Listening on http://{}", listener.local_addr()?);
+    serve(listener, config, Limits::default()).await?;
+    Ok(())
+}
+
+/// 在已经绑定好的 listener 上处理请求，直到出错为止。
+/// 测试中可以绑定 127.0.0.1:0 让系统分配端口。
+pub async fn serve(listener: TcpListener, config: Config, limits: Limits) -> io::Result<()> {
+    let config = Arc::new(config);
+    let limits = Arc::new(limits);
+    let connections = Arc::new(Semaphore::new(limits.max_connections));
+    let rejections = Arc::new(Semaphore::new(limits.max_connections));
+
+    loop {
+        let (mut stream, _) = listener.accept().await?;
+        let config = config.clone();
+        let limits = limits.clone();
+
+        // 与 thread.rs 中的用法一样，用信号量限制并发数，拿不到许可时不排队，直接拒绝
+        let permit = match connections.clone().try_acquire_owned() {
+            Ok(permit) => permit,
+            Err(_) => {
+                // 返回 503 的任务也由信号量限制，大量涌入的连接不会创建无限多的任务，
+                // 两个信号量都用完时在 accept 循环中直接关闭连接
+                let Ok(permit) = rejections.clone().try_acquire_owned() else {
+                    drop(stream);
+                    continue;
+                };
+                tokio::spawn(async move {
+                    // 先读掉请求再返回错误，否则直接关闭连接可能会让客户端收到 RST
+                    let _ = timeout(limits.read_timeout, read_head(&mut stream, &limits)).await;
+                    let _ = write_error(&mut stream, &limits, 503, "Too many connections").await;
+                    drop(permit);
+                });
+                continue;
+            }
+        };
+
+        tokio::spawn(async move {
+            if let Err(err) = handle(&mut stream, &config, &limits).await {
+                eprintln!("Failed to handle request: {}", err);
+            }
+            drop(permit);
+        });
+    }
+}
+
+async fn handle(stream: &mut TcpStream, config: &Config, limits: &Limits) -> io::Result<()> {
+    let head = match timeout(limits.read_timeout, read_head(stream, limits)).await {
+        Ok(Ok(Some(head))) => head,
+        Ok(Ok(None)) => return write_error(stream, limits, 431, "Request header too large").await,
+        Ok(Err(err)) => return Err(err),
+        Err(_) => return write_error(stream, limits, 408, "Request timeout").await,
+    };
+
+    let request_line = head.lines().next().unwrap_or_default();
+    let mut parts = request_line.split_whitespace();
+    let (method, target) = match (parts.next(), parts.next()) {
+        (Some(method), Some(target)) => (method, target),
+        _ => return write_error(stream, limits, 400, "Bad request").await,
+    };
+    if method != "GET" {
+        return write_error(stream, limits, 405, "Only GET is supported").await;
+    }
+
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    if path != "/search" {
+        return write_error(stream, limits, 404, "Not found").await;
+    }
+    let params = parse_query(query);
+    let key = match params.get("q") {
+        Some(key) if !key.is_empty() => key.clone(),
+        _ => return write_error(stream, limits, 400, "Missing query parameter q").await,
+    };
+    if key.chars().count() > limits.max_query_len {
+        return write_error(stream, limits, 414, "Query too long").await;
+    }
+
+    // 服务长时间运行，期间被搜索的文件（比如日志）随时可能被改写，所以从不映射文件
+    let config = Config {
+        key,
+        ignore_case: params.get("ignore_case").is_some_and(|v| v == "1"),
+        mmap_threshold: None,
+        ..config.clone()
+    };
+    search(stream, config, limits).await
+}
+
+// 读取请求头，直到遇到空行；超过大小限制时返回 None
+async fn read_head(stream: &mut TcpStream, limits: &Limits) -> io::Result<Option<String>> {
+    let mut head = Vec::new();
+    let mut buf = [0; 1024];
+    while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+        if head.len() > limits.max_request_bytes {
+            return Ok(None);
+        }
+        let n = stream.read(&mut buf).await?;
+        if n == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        head.extend_from_slice(&buf[..n]);
+    }
+    Ok(Some(String::from_utf8_lossy(&head).into_owned()))
+}
+
+// 逐个文件搜索，每得到一个文件的结果就立即作为一个 chunk 发送出去
+async fn search(stream: &mut TcpStream, mut config: Config, limits: &Limits) -> io::Result<()> {
+    write_bytes(
+        stream,
+        limits,
+        b"HTTP/1.1 200 OK\r\n\
+Content-Type: application/x-ndjson\r\n\
+Transfer-Encoding: chunked\r\n\
+Connection: close\r\n\r\n",
+    )
+    .await?;
+
+    let start = Instant::now();
+    let mut stats = Stats::default();
+    let roots: Vec<PathBuf> = config.files.iter().map(PathBuf::from).collect();
+    // 服务模式不会取消 config.cancel，这里总是能拿到完整的文件列表
+    let files = collect_files(&config.files, &config.cancel, &mut stats).unwrap_or_default();
+    let mut remaining = limits.max_results;
+
+    for path in files {
+        if remaining == 0 {
+            break;
+        }
+        config.max_count = Some(remaining);
+
+        // 文件读取是阻塞操作，放到专门的线程池中执行，避免阻塞运行时
+        let task_config = config.clone();
+        let task_path = path.clone();
+        let (found, file_stats) = tokio::task::spawn_blocking(move || {
+            let mut stats = Stats::default();
+            let found = search_path(&task_config, &task_path, &mut stats);
+            (found, stats)
+        })
+        .await
+        .map_err(io::Error::other)?;
+
+        stats.bytes_scanned += file_stats.bytes_scanned;
+        stats.lines_scanned += file_stats.lines_scanned;
+        stats.matched_lines += file_stats.matched_lines;
+        stats.matches += file_stats.matches;
+        remaining -= file_stats.matched_lines.min(remaining);
+
+        let found = match found {
+            Ok(found) => found,
+            Err(_) => {
+                stats.files_skipped += 1;
+                continue;
+            }
+        };
+        stats.files_searched += 1;
+        if found.is_empty() {
+            continue;
+        }
+
+        // 返回相对于搜索目录的路径，不暴露服务器上的绝对路径
+        let relative = roots
+            .iter()
+            .find_map(|root| path.strip_prefix(root).ok())
+            .filter(|p| !p.as_os_str().is_empty())
+            .unwrap_or(&path);
+        write_chunk(stream, limits, &(output::to_json(&found, relative) + "\n")).await?;
+    }
+
+    stats.elapsed = start.elapsed();
+    write_chunk(stream, limits, &(stats.to_json() + "\n")).await?;
+    write_bytes(stream, limits, b"0\r\n\r\n").await?;
+    shutdown(stream, limits).await
+}
+
+async fn write_chunk(stream: &mut TcpStream, limits: &Limits, data: &str) -> io::Result<()> {
+    let chunk = format!("{:x}\r\n{}\r\n", data.len(), data);
+    write_bytes(stream, limits, chunk.as_bytes()).await
+}
+
+// 客户端不读取时发送缓冲区会被写满，write_all 会一直等待下去，所以每次写入都有超时，
+// 超时后返回错误，调用方随之关闭连接
+async fn write_bytes(stream: &mut TcpStream, limits: &Limits, data: &[u8]) -> io::Result<()> {
+    with_write_timeout(limits, async {
+        stream.write_all(data).await?;
+        stream.flush().await
+    })
+    .await
+}
+
+async fn shutdown(stream: &mut TcpStream, limits: &Limits) -> io::Result<()> {
+    with_write_timeout(limits, stream.shutdown()).await
+}
+
+async fn with_write_timeout(
+    limits: &Limits,
+    write: impl Future<Output = io::Result<()>>,
+) -> io::Result<()> {
+    match timeout(limits.write_timeout, write).await {
+        Ok(result) => result,
+        Err(_) => Err(io::Error::new(io::ErrorKind::TimedOut, "Write timed out")),
+    }
+}
+
+async fn write_error(
+    stream: &mut TcpStream,
+    limits: &Limits,
+    status: u16,
+    message: &str,
+) -> io::Result<()> {
+    let reason = match status {
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        408 => "Request Timeout",
+        414 => "URI Too Long",
+        431 => "Request Header Fields Too Large",
+        503 => "Service Unavailable",
+        _ => "Error",
+    };
+    let body = format!("{{\"error\":{}}}\n", json_string(message));
+    let response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        body.len(),
+        body
+    );
+    write_bytes(stream, limits, response.as_bytes()).await?;
+    shutdown(stream, limits).await
+}
+
+/// 解析 `a=1&b=%E4%BD%A0` 形式的查询字符串
+pub fn parse_query(query: &str) -> HashMap<String, String> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(name), percent_decode(value))
+        })
+        .collect()
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' => match s
+                .get(i + 1..i + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            {
+                Some(b) => {
+                    decoded.push(b);
+                    i += 2;
+                }
+                None => decoded.push(b'%'),
+            },
+            b => decoded.push(b),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 通过网络的测试在 tests/server.rs 中，只使用公开的 serve 和 Limits
+    #[test]
+    fn decode_query() {
+        let params = parse_query("q=a+b%21&ignore_case=1&empty");
+        assert_eq!(params["q"], "a b!");
+        assert_eq!(params["ignore_case"], "1");
+        assert_eq!(params["empty"], "");
+        assert_eq!(percent_decode("100%"), "100%");
+    }
+}
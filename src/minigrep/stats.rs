@@ -0,0 +1,88 @@
+/// 一次搜索的统计信息，用于 --stats 选项
+use std::fmt;
+use std::time::Duration;
+
+use super::output::json_string;
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Stats {
+    pub files_searched: usize,
+    // 无法打开、无法读取或者不是 UTF-8 文本的文件会被跳过
+    pub files_skipped: usize,
+    pub bytes_scanned: usize,
+    pub lines_scanned: usize,
+    pub matched_lines: usize,
+    // 一行中可能有多次匹配，因此 matches 可能大于 matched_lines
+    pub matches: usize,
+    // 搜索因为超时或者被取消而提前结束，此时的结果和统计都只是一部分
+    pub cancelled: bool,
+    pub elapsed: Duration,
+}
+
+impl Stats {
+    pub fn to_json(&self) -> String {
+        let fields = [
+            ("files_searched", self.files_searched.to_string()),
+            ("files_skipped", self.files_skipped.to_string()),
+            ("bytes_scanned", self.bytes_scanned.to_string()),
+            ("lines_scanned", self.lines_scanned.to_string()),
+            ("matched_lines", self.matched_lines.to_string()),
+            ("matches", self.matches.to_string()),
+            ("cancelled", self.cancelled.to_string()),
+            (
+                "elapsed_ms",
+                format!("{:.3}", self.elapsed.as_secs_f64() * 1000.0),
+            ),
+        ];
+        let fields: Vec<String> = fields
+            .iter()
+            .map(|(name, value)| format!("{}:{}", json_string(name), value))
+            .collect();
+        format!("{{\"stats\":{{{}}}}}", fields.join(","))
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Stats:")?;
+        writeln!(f, "  files searched: {}", self.files_searched)?;
+        writeln!(f, "  files skipped:  {}", self.files_skipped)?;
+        writeln!(f, "  bytes scanned:  {}", self.bytes_scanned)?;
+        writeln!(f, "  lines scanned:  {}", self.lines_scanned)?;
+        writeln!(f, "  matched lines:  {}", self.matched_lines)?;
+        writeln!(f, "  matches:        {}", self.matches)?;
+        writeln!(f, "  cancelled:      {}", self.cancelled)?;
+        write!(f, "  elapsed:        {:?}", self.elapsed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stats_report() {
+        let stats = Stats {
+            files_searched: 2,
+            files_skipped: 1,
+            bytes_scanned: 100,
+            lines_scanned: 10,
+            matched_lines: 3,
+            matches: 4,
+            cancelled: true,
+            elapsed: Duration::from_micros(1500),
+        };
+
+        assert_eq!(
+            stats.to_json(),
+            "{\"stats\":{\"files_searched\":2,\"files_skipped\":1,\"bytes_scanned\":100,\
+\"lines_scanned\":10,\"matched_lines\":3,\"matches\":4,\"cancelled\":true,\"elapsed_ms\":1.500}}"
+        );
+
+        let text = stats.to_string();
+        assert!(text.starts_with("Stats:\n"));
+        assert!(text.contains("matches:        4"));
+        assert!(text.contains("cancelled:      true"));
+        assert!(text.ends_with("elapsed:        1.5ms"));
+    }
+}
@@ -0,0 +1,196 @@
+// minigrep 的 HTTP 服务模式：在随机端口上启动服务，通过 TCP 发送真实的请求
+use std::time::Duration;
+
+use hello_world::minigrep::server::{serve, Limits};
+use hello_world::minigrep::Config;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+// 启动一个监听在随机端口上的服务，返回它的地址
+async fn start(limits: Limits) -> String {
+    start_in("src/examples", limits).await
+}
+
+async fn start_in(dir: &str, limits: Limits) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+    let config = Config {
+        files: vec![dir.to_string()],
+        ..Default::default()
+    };
+    tokio::spawn(serve(listener, config, limits));
+    addr
+}
+
+async fn request(addr: &str, request: &str) -> String {
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    stream.write_all(request.as_bytes()).await.unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    response
+}
+
+async fn get(addr: &str, target: &str) -> String {
+    request(
+        addr,
+        &format!("GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", target),
+    )
+    .await
+}
+
+// 把 chunked 编码的响应体还原成一行一行的 JSON
+fn body_lines(response: &str) -> Vec<String> {
+    let (head, mut body) = response.split_once("\r\n\r\n").unwrap();
+    assert!(head.starts_with("HTTP/1.1 200 OK"));
+    assert!(head.contains("Transfer-Encoding: chunked"));
+
+    let mut data = String::new();
+    loop {
+        let (size, rest) = body.split_once("\r\n").unwrap();
+        let size = usize::from_str_radix(size, 16).unwrap();
+        if size == 0 {
+            break;
+        }
+        data.push_str(&rest[..size]);
+        body = &rest[size + 2..];
+    }
+    data.lines().map(String::from).collect()
+}
+
+#[tokio::test]
+async fn search_over_http() {
+    let addr = start(Limits::default()).await;
+
+    let lines = body_lines(&get(&addr, "/search?q=nobody").await);
+    assert_eq!(lines.len(), 2);
+    assert_eq!(
+        lines[0],
+        r#"{"path":"test.txt","results":["I'm nobody! Who are you?","Are you nobody, too?"]}"#
+    );
+    assert!(lines[1].starts_with(r#"{"stats":{"files_searched":2,"#));
+
+    let lines = body_lines(&get(&addr, "/search?q=HOW+PUBLIC&ignore_case=1").await);
+    assert_eq!(
+        lines[0],
+        r#"{"path":"test.txt","results":["How public, like a frog"]}"#
+    );
+
+    // 中文需要经过百分号编码
+    let lines = body_lines(&get(&addr, "/search?q=%E9%9D%92%E8%9B%99").await);
+    assert_eq!(
+        lines[0],
+        r#"{"path":"test.txt","results":["他们就像青蛙一样呱噪，"]}"#
+    );
+}
+
+#[tokio::test]
+async fn request_limits() {
+    let addr = start(Limits {
+        max_query_len: 8,
+        max_results: 1,
+        ..Limits::default()
+    })
+    .await;
+
+    let lines = body_lines(&get(&addr, "/search?q=you").await);
+    assert_eq!(
+        lines[0],
+        r#"{"path":"test.txt","results":["I'm nobody! Who are you?"]}"#
+    );
+
+    let response = get(&addr, "/search?q=123456789").await;
+    assert!(response.starts_with("HTTP/1.1 414"));
+    let response = get(&addr, "/search").await;
+    assert!(response.starts_with("HTTP/1.1 400"));
+    let response = get(&addr, "/files").await;
+    assert!(response.starts_with("HTTP/1.1 404"));
+    let response = request(&addr, "POST /search?q=a HTTP/1.1\r\n\r\n").await;
+    assert!(response.starts_with("HTTP/1.1 405"));
+
+    let huge = format!(
+        "GET /search?q=a HTTP/1.1\r\nX: {}\r\n\r\n",
+        "a".repeat(10000)
+    );
+    assert!(request(&addr, &huge).await.starts_with("HTTP/1.1 431"));
+}
+
+#[tokio::test]
+async fn too_many_connections() {
+    let addr = start(Limits {
+        max_connections: 1,
+        read_timeout: Duration::from_millis(500),
+        ..Limits::default()
+    })
+    .await;
+
+    // 第一个连接不发送请求，一直占用唯一的许可，直到读取超时
+    let _idle = TcpStream::connect(&addr).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    let response = get(&addr, "/search?q=you").await;
+    assert!(response.starts_with("HTTP/1.1 503"));
+}
+
+#[tokio::test]
+async fn rejections_are_bounded() {
+    let addr = start(Limits {
+        max_connections: 1,
+        read_timeout: Duration::from_secs(5),
+        ..Limits::default()
+    })
+    .await;
+
+    // 第一个连接占用处理请求的许可，第二个连接占用返回 503 的许可，两个都不发送请求
+    let _idle = TcpStream::connect(&addr).await.unwrap();
+    let _rejected = TcpStream::connect(&addr).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    // 再来的连接不会等到读取超时，在 accept 之后立即被关闭
+    let mut stream = TcpStream::connect(&addr).await.unwrap();
+    let mut buf = [0; 64];
+    let read = tokio::time::timeout(Duration::from_secs(1), stream.read(&mut buf))
+        .await
+        .expect("connection should be closed right away");
+    assert!(matches!(read, Ok(0) | Err(_)));
+}
+
+#[tokio::test]
+async fn slow_readers_are_dropped() {
+    // 响应远大于发送和接收缓冲区，客户端不读取时服务端的写入会一直阻塞
+    let dir = std::env::temp_dir().join(format!("minigrep-server-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let line = format!("rust {}\n", "x".repeat(120));
+    std::fs::write(dir.join("big.txt"), line.repeat(300_000)).unwrap();
+
+    let addr = start_in(
+        dir.to_str().unwrap(),
+        Limits {
+            max_connections: 1,
+            max_results: usize::MAX,
+            write_timeout: Duration::from_millis(300),
+            ..Limits::default()
+        },
+    )
+    .await;
+
+    // 第一个客户端发送请求以后不再读取，写入超时后服务端关闭连接，释放唯一的许可
+    let mut slow = TcpStream::connect(&addr).await.unwrap();
+    slow.write_all(b"GET /search?q=rust HTTP/1.1\r\n\r\n")
+        .await
+        .unwrap();
+    // 搜索大文件需要一些时间，在此期间新的连接都会收到 503
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(20);
+    loop {
+        let response = get(&addr, "/search?q=nothing").await;
+        if response.starts_with("HTTP/1.1 200") {
+            break;
+        }
+        assert!(response.starts_with("HTTP/1.1 503"));
+        assert!(
+            tokio::time::Instant::now() < deadline,
+            "the slow reader was never dropped"
+        );
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    drop(slow);
+    std::fs::remove_dir_all(&dir).unwrap();
+}